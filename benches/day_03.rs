@@ -0,0 +1,49 @@
+//! Compares the day 3 instruction finders on the real input.
+//!
+//! Run with `cargo +nightly bench --bench day_03`.
+
+#![feature(test)]
+
+extern crate test;
+
+use aoc2024::{run_variant, Day, Part};
+use test::Bencher;
+
+const INPUT: &str = include_str!("../input/day_3");
+
+fn bench(b: &mut Bencher, part: Part, variant: &str) {
+    let day: Day = 3.try_into().unwrap();
+    let input = INPUT.repeat(10);
+    b.bytes = input.len() as u64;
+    b.iter(|| run_variant(day, part, test::black_box(&input), variant).unwrap());
+}
+
+#[bench]
+fn part_1_regex(b: &mut Bencher) {
+    bench(b, Part::Part1, "regex");
+}
+
+#[bench]
+fn part_1_nom(b: &mut Bencher) {
+    bench(b, Part::Part1, "nom");
+}
+
+#[bench]
+fn part_1_bytes(b: &mut Bencher) {
+    bench(b, Part::Part1, "bytes");
+}
+
+#[bench]
+fn part_2_regex(b: &mut Bencher) {
+    bench(b, Part::Part2, "regex");
+}
+
+#[bench]
+fn part_2_nom(b: &mut Bencher) {
+    bench(b, Part::Part2, "nom");
+}
+
+#[bench]
+fn part_2_bytes(b: &mut Bencher) {
+    bench(b, Part::Part2, "bytes");
+}
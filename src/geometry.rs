@@ -0,0 +1,499 @@
+//! Points and directions on the integer lattice.
+//!
+//! Screen coordinates are used throughout: `x` goes right and `y` goes *down*, so turning right
+//! means turning clockwise as seen on the puzzle input.
+
+use std::{
+    fmt::{self, Debug, Display},
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+
+/// The signed integer types points can be made of
+pub trait Coord:
+    Copy
+    + Debug
+    + Default
+    + Ord
+    + std::hash::Hash
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + TryFrom<usize>
+    + TryInto<usize>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn abs(self) -> Self;
+}
+
+macro_rules! impl_coord {
+    ($($t:ty),*) => {
+        $(
+            impl Coord for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                fn abs(self) -> Self {
+                    <$t>::abs(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_coord!(i8, i16, i32, i64, i128, isize);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point2<T = i32> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T: Coord> Point2<T> {
+    pub const ORIGIN: Self = Self {
+        x: T::ZERO,
+        y: T::ZERO,
+    };
+
+    pub fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+
+    pub fn manhattan(&self, other: &Self) -> T {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    /// The king's move distance, diagonal steps count as one
+    pub fn chebyshev(&self, other: &Self) -> T {
+        (self.x - other.x).abs().max((self.y - other.y).abs())
+    }
+
+    pub fn dot(&self, other: &Self) -> T {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// The `z` component of the 3D cross product, positive if `other` is clockwise of `self`
+    pub fn cross(&self, other: &Self) -> T {
+        self.x * other.y - self.y * other.x
+    }
+
+    /// Rotates by 90° counter-clockwise around the origin
+    pub fn rotate_left(self) -> Self {
+        Self {
+            x: self.y,
+            y: -self.x,
+        }
+    }
+
+    /// Rotates by 90° clockwise around the origin
+    pub fn rotate_right(self) -> Self {
+        Self {
+            x: -self.y,
+            y: self.x,
+        }
+    }
+
+    /// The orthogonal neighbours, right, down, left and up
+    pub fn neighbors4(self) -> impl Iterator<Item = Self> {
+        Cardinal::iterator().map(move |d| self + d.into())
+    }
+
+    /// The orthogonal and diagonal neighbours
+    pub fn neighbors8(self) -> impl Iterator<Item = Self> {
+        Direction::iterator().map(move |d| self + d.into())
+    }
+
+    /// The point as `(x, y)` grid indices, `None` if either is negative or too large
+    pub fn to_grid(self) -> Option<(usize, usize)> {
+        Some((self.x.try_into().ok()?, self.y.try_into().ok()?))
+    }
+
+    /// The point at the given `(x, y)` grid indices, `None` if they don't fit into `T`
+    pub fn from_grid((x, y): (usize, usize)) -> Option<Self> {
+        Some(Self {
+            x: x.try_into().ok()?,
+            y: y.try_into().ok()?,
+        })
+    }
+}
+
+impl<T: Coord> Add for Point2<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+        }
+    }
+}
+
+impl<T: Coord> Sub for Point2<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+        }
+    }
+}
+
+impl<T: Coord> Mul<T> for Point2<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Self {
+            x: self.x * rhs,
+            y: self.y * rhs,
+        }
+    }
+}
+
+impl<T: Coord> Neg for Point2<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            x: -self.x,
+            y: -self.y,
+        }
+    }
+}
+
+impl<T: Coord> AddAssign for Point2<T> {
+    fn add_assign(&mut self, rhs: Self) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+    }
+}
+
+impl<T: Coord> SubAssign for Point2<T> {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.x -= rhs.x;
+        self.y -= rhs.y;
+    }
+}
+
+impl<T: Coord> From<(T, T)> for Point2<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T: Display> Display for Point2<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point3<T = i32> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T: Coord> Point3<T> {
+    pub const ORIGIN: Self = Self {
+        x: T::ZERO,
+        y: T::ZERO,
+        z: T::ZERO,
+    };
+
+    pub fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn manhattan(&self, other: &Self) -> T {
+        (self.x - other.x).abs() + (self.y - other.y).abs() + (self.z - other.z).abs()
+    }
+
+    pub fn chebyshev(&self, other: &Self) -> T {
+        (self.x - other.x)
+            .abs()
+            .max((self.y - other.y).abs())
+            .max((self.z - other.z).abs())
+    }
+
+    pub fn dot(&self, other: &Self) -> T {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(&self, other: &Self) -> Self {
+        Self {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+}
+
+impl<T: Coord> Add for Point3<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            z: self.z + rhs.z,
+        }
+    }
+}
+
+impl<T: Coord> Sub for Point3<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            z: self.z - rhs.z,
+        }
+    }
+}
+
+impl<T: Coord> Mul<T> for Point3<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Self {
+            x: self.x * rhs,
+            y: self.y * rhs,
+            z: self.z * rhs,
+        }
+    }
+}
+
+impl<T: Coord> Neg for Point3<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
+impl<T: Display> Display for Point3<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{}", self.x, self.y, self.z)
+    }
+}
+
+/// All eight directions, going counter-clockwise starting with `Front` (to the right)
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Direction {
+    Front,
+    UpFront,
+    Up,
+    UpBack,
+    Back,
+    DownBack,
+    Down,
+    DownFront,
+}
+
+impl Direction {
+    const ALL: [Direction; 8] = {
+        use Direction::*;
+        [Front, UpFront, Up, UpBack, Back, DownBack, Down, DownFront]
+    };
+
+    pub fn iterator() -> impl Iterator<Item = Direction> {
+        Self::ALL.into_iter()
+    }
+
+    pub fn opposite(&self) -> Direction {
+        self.turn(4)
+    }
+
+    /// Turns by 45° counter-clockwise
+    pub fn turn_left(&self) -> Direction {
+        self.turn(1)
+    }
+
+    /// Turns by 45° clockwise
+    pub fn turn_right(&self) -> Direction {
+        self.turn(7)
+    }
+
+    fn turn(&self, eighths: usize) -> Direction {
+        Self::ALL[(*self as usize + eighths) % 8]
+    }
+}
+
+impl<T: Coord> From<Direction> for Point2<T> {
+    fn from(value: Direction) -> Self {
+        let (one, zero) = (T::ONE, T::ZERO);
+        let (x, y) = match value {
+            Direction::Front => (one, zero),
+            Direction::UpFront => (one, -one),
+            Direction::Up => (zero, -one),
+            Direction::UpBack => (-one, -one),
+            Direction::Back => (-one, zero),
+            Direction::DownBack => (-one, one),
+            Direction::Down => (zero, one),
+            Direction::DownFront => (one, one),
+        };
+        Point2 { x, y }
+    }
+}
+
+/// The four directions along the axes, in clockwise order
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Cardinal {
+    Right,
+    Down,
+    Left,
+    Up,
+}
+
+impl Cardinal {
+    const ALL: [Cardinal; 4] = [
+        Cardinal::Right,
+        Cardinal::Down,
+        Cardinal::Left,
+        Cardinal::Up,
+    ];
+
+    pub fn iterator() -> impl Iterator<Item = Cardinal> {
+        Self::ALL.into_iter()
+    }
+
+    pub fn opposite(&self) -> Cardinal {
+        self.turn(2)
+    }
+
+    /// Turns by 90° counter-clockwise
+    pub fn turn_left(&self) -> Cardinal {
+        self.turn(3)
+    }
+
+    /// Turns by 90° clockwise
+    pub fn turn_right(&self) -> Cardinal {
+        self.turn(1)
+    }
+
+    fn turn(&self, quarters: usize) -> Cardinal {
+        Self::ALL[(*self as usize + quarters) % 4]
+    }
+}
+
+impl From<Cardinal> for Direction {
+    fn from(value: Cardinal) -> Self {
+        match value {
+            Cardinal::Right => Direction::Front,
+            Cardinal::Down => Direction::Down,
+            Cardinal::Left => Direction::Back,
+            Cardinal::Up => Direction::Up,
+        }
+    }
+}
+
+impl<T: Coord> From<Cardinal> for Point2<T> {
+    fn from(value: Cardinal) -> Self {
+        Direction::from(value).into()
+    }
+}
+
+impl TryFrom<char> for Cardinal {
+    type Error = color_eyre::eyre::Error;
+
+    /// Arrows as used in most puzzles, `^>v<`
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            '>' => Ok(Cardinal::Right),
+            'v' => Ok(Cardinal::Down),
+            '<' => Ok(Cardinal::Left),
+            '^' => Ok(Cardinal::Up),
+            _ => Err(color_eyre::eyre::eyre!("{value:?} is not a direction")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test]
+    fn test_ops() {
+        let a = Point2::new(1, 2);
+        let b = Point2::new(-3, 5);
+        assert_eq!(a + b, Point2::new(-2, 7));
+        assert_eq!(a - b, Point2::new(4, -3));
+        assert_eq!(a * 3, Point2::new(3, 6));
+        assert_eq!(-a, Point2::new(-1, -2));
+        assert_eq!(a.dot(&b), 7);
+        assert_eq!(a.cross(&b), 11);
+
+        let mut c = a;
+        c += b;
+        c -= a;
+        assert_eq!(c, b);
+    }
+
+    #[test_case(Point2::new(0, 0), Point2::new(3, -4), 7, 4)]
+    #[test_case(Point2::new(-2, -2), Point2::new(-2, -2), 0, 0)]
+    #[test_case(Point2::new(5, 1), Point2::new(-5, 2), 11, 10)]
+    fn test_distances(a: Point2<i64>, b: Point2<i64>, manhattan: i64, chebyshev: i64) {
+        assert_eq!(a.manhattan(&b), manhattan);
+        assert_eq!(b.manhattan(&a), manhattan);
+        assert_eq!(a.chebyshev(&b), chebyshev);
+    }
+
+    #[test]
+    fn test_rotation() {
+        let p = Point2::<i32>::from(Cardinal::Right);
+        assert_eq!(p.rotate_right(), Cardinal::Down.into());
+        assert_eq!(p.rotate_left(), Cardinal::Up.into());
+        assert_eq!(p.rotate_left().rotate_left(), -p);
+
+        for c in Cardinal::iterator() {
+            let p: Point2 = c.into();
+            assert_eq!(p.rotate_right(), c.turn_right().into());
+            assert_eq!(p.rotate_left(), c.turn_left().into());
+            assert_eq!(-p, c.opposite().into());
+        }
+    }
+
+    #[test]
+    fn test_directions() {
+        for d in Direction::iterator() {
+            assert_eq!(d.opposite().opposite(), d);
+            assert_eq!(d.turn_left().turn_right(), d);
+            let p: Point2<i8> = d.into();
+            assert_eq!(-p, d.opposite().into());
+        }
+        assert_eq!(Direction::Front.turn_left(), Direction::UpFront);
+        assert_eq!(Direction::Front.turn_right(), Direction::DownFront);
+        assert_eq!(Point2::new(4, 4).neighbors8().count(), 8);
+        assert_eq!(Point2::new(4, 4).neighbors4().count(), 4);
+    }
+
+    #[test]
+    fn test_grid_conversion() {
+        assert_eq!(Point2::new(3, 4).to_grid(), Some((3, 4)));
+        assert_eq!(Point2::new(-1, 4).to_grid(), None);
+        assert_eq!(Point2::<i32>::from_grid((7, 8)), Some(Point2::new(7, 8)));
+        assert_eq!(Point2::<i8>::from_grid((300, 8)), None);
+    }
+
+    #[test]
+    fn test_point3() {
+        let a = Point3::new(1, 0, 0);
+        let b = Point3::new(0, 1, 0);
+        assert_eq!(a.cross(&b), Point3::new(0, 0, 1));
+        assert_eq!(a.dot(&b), 0);
+        assert_eq!(a.manhattan(&-b), 2);
+        assert_eq!(Point3::new(1, -7, 3).chebyshev(&Point3::ORIGIN), 7);
+        assert_eq!(a + b - a * 2, Point3::new(-1, 1, 0));
+    }
+}
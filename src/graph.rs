@@ -0,0 +1,508 @@
+//! Adjacency-list graphs and the usual algorithms on them.
+//!
+//! Nodes can be anything hashable, internally they are numbered in insertion order. Wherever
+//! `pathfinding` already has the algorithm, it does the actual work on those numbers.
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    ops::Add,
+};
+
+use color_eyre::eyre::{eyre, Result};
+use pathfinding::{num_traits::Zero, prelude as pf};
+
+use crate::grid::Grid;
+
+/// Edge costs, anything `pathfinding` can add up and compare
+pub trait Cost: Zero + Ord + Copy + Add<Output = Self> {}
+
+impl<T: Zero + Ord + Copy + Add<Output = T>> Cost for T {}
+
+#[derive(Debug, Clone)]
+pub struct Graph<N, C = usize> {
+    nodes: Vec<N>,
+    ids: HashMap<N, usize>,
+    edges: Vec<Vec<(usize, C)>>,
+    /// Incoming edges, only kept for directed graphs
+    reverse: Vec<Vec<(usize, C)>>,
+    directed: bool,
+}
+
+/// All shortest paths between two nodes, as the DAG of every edge on any of them
+#[derive(Debug, Clone)]
+pub struct ShortestPaths<N, C> {
+    pub cost: C,
+    pub nodes: HashSet<N>,
+    pub edges: HashSet<(N, N)>,
+}
+
+impl<N: Eq + Hash + Clone, C: Cost> Graph<N, C> {
+    pub fn directed() -> Self {
+        Self::empty(true)
+    }
+
+    pub fn undirected() -> Self {
+        Self::empty(false)
+    }
+
+    fn empty(directed: bool) -> Self {
+        Self {
+            nodes: vec![],
+            ids: HashMap::new(),
+            edges: vec![],
+            reverse: vec![],
+            directed,
+        }
+    }
+
+    /// Builds a graph from `(from, to, cost)` triples
+    pub fn from_weighted_edges(directed: bool, edges: impl IntoIterator<Item = (N, N, C)>) -> Self {
+        let mut g = Self::empty(directed);
+        for (a, b, c) in edges {
+            g.add_edge(a, b, c);
+        }
+        g
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.nodes.iter()
+    }
+
+    pub fn contains(&self, node: &N) -> bool {
+        self.ids.contains_key(node)
+    }
+
+    /// Adds a node without any edges, does nothing if it is already there
+    pub fn add_node(&mut self, node: N) -> usize {
+        if let Some(&id) = self.ids.get(&node) {
+            return id;
+        }
+        let id = self.nodes.len();
+        self.ids.insert(node.clone(), id);
+        self.nodes.push(node);
+        self.edges.push(vec![]);
+        self.reverse.push(vec![]);
+        id
+    }
+
+    /// Adds an edge, both ways for undirected graphs. Missing nodes are added on the fly.
+    pub fn add_edge(&mut self, from: N, to: N, cost: C) {
+        let (a, b) = (self.add_node(from), self.add_node(to));
+        self.edges[a].push((b, cost));
+        if self.directed {
+            self.reverse[b].push((a, cost));
+        } else if a != b {
+            self.edges[b].push((a, cost));
+        }
+    }
+
+    /// Outgoing edges of `node` with their costs
+    pub fn neighbors<'a>(&'a self, node: &N) -> impl Iterator<Item = (&'a N, C)> + 'a {
+        self.ids
+            .get(node)
+            .map(|&id| self.edges[id].as_slice())
+            .unwrap_or(&[])
+            .iter()
+            .map(|&(n, c)| (&self.nodes[n], c))
+    }
+
+    fn id(&self, node: &N) -> Result<usize> {
+        self.ids
+            .get(node)
+            .copied()
+            .ok_or_else(|| eyre!("node is not part of the graph"))
+    }
+
+    fn path(&self, ids: Vec<usize>) -> Vec<N> {
+        ids.into_iter().map(|i| self.nodes[i].clone()).collect()
+    }
+
+    /// A path with the fewest edges, ignoring costs
+    pub fn bfs(&self, start: &N, goal: &N) -> Result<Option<Vec<N>>> {
+        let (start, goal) = (self.id(start)?, self.id(goal)?);
+        let path = pf::bfs(
+            &start,
+            |&n| self.edges[n].iter().map(|&(m, _)| m),
+            |&n| n == goal,
+        );
+        Ok(path.map(|p| self.path(p)))
+    }
+
+    /// The cheapest path and its cost
+    pub fn dijkstra(&self, start: &N, goal: &N) -> Result<Option<(Vec<N>, C)>> {
+        self.astar(start, goal, |_| C::zero())
+    }
+
+    /// The cheapest path and its cost, `heuristic` must never overestimate the remaining cost
+    pub fn astar(
+        &self,
+        start: &N,
+        goal: &N,
+        heuristic: impl Fn(&N) -> C,
+    ) -> Result<Option<(Vec<N>, C)>> {
+        let (start, goal) = (self.id(start)?, self.id(goal)?);
+        let path = pf::astar(
+            &start,
+            |&n| self.edges[n].iter().copied(),
+            |&n| heuristic(&self.nodes[n]),
+            |&n| n == goal,
+        );
+        Ok(path.map(|(p, c)| (self.path(p), c)))
+    }
+
+    /// Cost of the cheapest path from `start` to every reachable node
+    pub fn distances(&self, start: &N) -> Result<HashMap<N, C>> {
+        let start = self.id(start)?;
+        Ok(self
+            .distances_by_id(start, false)
+            .into_iter()
+            .map(|(n, c)| (self.nodes[n].clone(), c))
+            .collect())
+    }
+
+    fn distances_by_id(&self, start: usize, backwards: bool) -> HashMap<usize, C> {
+        let edges = if backwards && self.directed {
+            &self.reverse
+        } else {
+            &self.edges
+        };
+        let mut dist: HashMap<_, _> = pf::dijkstra_all(&start, |&n| edges[n].iter().copied())
+            .into_iter()
+            .map(|(n, (_, c))| (n, c))
+            .collect();
+        dist.insert(start, C::zero());
+        dist
+    }
+
+    /// Every node and edge that lies on at least one cheapest path from `start` to `goal`.
+    ///
+    /// An edge `u -> v` is on a cheapest path exactly if getting to `u`, taking the edge and going
+    /// on from `v` to the goal adds up to the cheapest cost.
+    pub fn shortest_paths(&self, start: &N, goal: &N) -> Result<Option<ShortestPaths<N, C>>> {
+        let (s, g) = (self.id(start)?, self.id(goal)?);
+        let from_start = self.distances_by_id(s, false);
+        let to_goal = self.distances_by_id(g, true);
+        let Some(&cost) = from_start.get(&g) else {
+            return Ok(None);
+        };
+
+        let mut nodes = HashSet::new();
+        let mut edges = HashSet::new();
+        for (&u, &du) in from_start.iter() {
+            for &(v, c) in self.edges[u].iter() {
+                if to_goal.get(&v).is_some_and(|&dv| du + c + dv == cost) {
+                    nodes.insert(self.nodes[u].clone());
+                    nodes.insert(self.nodes[v].clone());
+                    edges.insert((self.nodes[u].clone(), self.nodes[v].clone()));
+                }
+            }
+        }
+        nodes.insert(start.clone());
+
+        Ok(Some(ShortestPaths { cost, nodes, edges }))
+    }
+
+    /// Connected components, for directed graphs edges count in both directions
+    pub fn components(&self) -> Vec<HashSet<N>> {
+        let all: Vec<_> = (0..self.len()).collect();
+        pf::connected_components(&all, |&n| {
+            self.edges[n]
+                .iter()
+                .chain(self.reverse[n].iter())
+                .map(|&(m, _)| m)
+                .collect::<Vec<_>>()
+        })
+        .into_iter()
+        .map(|c| c.into_iter().map(|n| self.nodes[n].clone()).collect())
+        .collect()
+    }
+
+    /// Orders the nodes of a directed graph so every edge points forward, fails on a cycle
+    pub fn topological_sort(&self) -> Result<Vec<N>> {
+        if !self.directed {
+            return Err(eyre!("only directed graphs can be sorted topologically"));
+        }
+        let all: Vec<_> = (0..self.len()).collect();
+        pf::topological_sort(&all, |&n| self.edges[n].iter().map(|&(m, _)| m))
+            .map(|p| self.path(p))
+            .map_err(|_| eyre!("graph contains a cycle"))
+    }
+
+    pub fn has_cycle(&self) -> bool {
+        if self.directed {
+            return self.topological_sort().is_err();
+        }
+        // a forest has exactly one edge less than nodes in each tree, anything more closes a loop
+        let edges = self
+            .edges
+            .iter()
+            .enumerate()
+            .map(|(a, e)| e.iter().filter(|&&(b, _)| a <= b).count())
+            .sum::<usize>();
+        edges + self.components().len() > self.len()
+    }
+
+    /// Every set of three nodes that are all connected with each other, each reported once.
+    /// Undirected graphs only.
+    pub fn triangles(&self) -> Result<Vec<[&N; 3]>> {
+        let neighbours = self.neighbour_sets("triangles")?;
+        let mut triangles = vec![];
+        for (a, na) in neighbours.iter().enumerate() {
+            for &b in na.iter().filter(|&&b| b > a) {
+                for &c in na.intersection(&neighbours[b]).filter(|&&c| c > b) {
+                    triangles.push([&self.nodes[a], &self.nodes[b], &self.nodes[c]]);
+                }
+            }
+        }
+        Ok(triangles)
+    }
+
+    /// The largest set of nodes that are all connected with each other, undirected graphs only
+    pub fn max_clique(&self) -> Result<Vec<N>> {
+        let neighbours = self.neighbour_sets("cliques")?;
+        let mut best = vec![];
+        bron_kerbosch(
+            &neighbours,
+            &mut vec![],
+            (0..self.len()).collect(),
+            HashSet::new(),
+            &mut best,
+        );
+        Ok(self.path(best))
+    }
+
+    /// Neighbours of every node without self loops, for the clique searches
+    fn neighbour_sets(&self, what: &str) -> Result<Vec<HashSet<usize>>> {
+        if self.directed {
+            return Err(eyre!("only undirected graphs have {what}"));
+        }
+        Ok(self
+            .edges
+            .iter()
+            .enumerate()
+            .map(|(a, e)| e.iter().map(|&(b, _)| b).filter(|&b| b != a).collect())
+            .collect())
+    }
+}
+
+impl<N: Eq + Hash + Clone> Graph<N, usize> {
+    /// Builds a graph where every edge costs 1
+    pub fn from_edges(directed: bool, edges: impl IntoIterator<Item = (N, N)>) -> Self {
+        Self::from_weighted_edges(directed, edges.into_iter().map(|(a, b)| (a, b, 1)))
+    }
+}
+
+impl Graph<(usize, usize), usize> {
+    /// Connects every `passable` cell of `grid` with its passable orthogonal neighbours
+    pub fn from_grid<T>(grid: &Grid<T>, passable: impl Fn(&T) -> bool) -> Self {
+        let mut g = Self::undirected();
+        for ((x, y), cell) in grid.cells().filter(|(_, c)| passable(c)) {
+            g.add_node((x, y));
+            // only look right and down, the other directions are covered from the other side
+            for (nx, ny) in [(x + 1, y), (x, y + 1)] {
+                if grid.get(nx, ny).is_some_and(&passable) {
+                    g.add_edge((x, y), (nx, ny), 1);
+                }
+            }
+        }
+        g
+    }
+}
+
+/// Bron–Kerbosch with pivoting, `r` is the clique so far, `p` the candidates that could extend it
+/// and `x` the ones that were already tried
+fn bron_kerbosch(
+    neighbours: &[HashSet<usize>],
+    r: &mut Vec<usize>,
+    mut p: HashSet<usize>,
+    mut x: HashSet<usize>,
+    best: &mut Vec<usize>,
+) {
+    if p.is_empty() {
+        if x.is_empty() && r.len() > best.len() {
+            *best = r.clone();
+        }
+        return;
+    }
+    // no clique in here can beat the one we already have
+    if r.len() + p.len() <= best.len() {
+        return;
+    }
+
+    let pivot = p
+        .union(&x)
+        .max_by_key(|&&u| neighbours[u].intersection(&p).count())
+        .copied()
+        .expect("p is not empty");
+
+    let candidates: Vec<_> = p.difference(&neighbours[pivot]).copied().collect();
+    for v in candidates {
+        r.push(v);
+        bron_kerbosch(
+            neighbours,
+            r,
+            p.intersection(&neighbours[v]).copied().collect(),
+            x.intersection(&neighbours[v]).copied().collect(),
+            best,
+        );
+        r.pop();
+        p.remove(&v);
+        x.insert(v);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_ok::assert_ok;
+    use itertools::Itertools;
+
+    /// ```text
+    /// a -1- b -1- d
+    ///  \         /
+    ///   2- c -1-/
+    ///       \
+    ///        5- e
+    /// ```
+    fn weighted() -> Graph<char> {
+        Graph::from_weighted_edges(
+            false,
+            [
+                ('a', 'b', 1),
+                ('b', 'd', 1),
+                ('a', 'c', 2),
+                ('c', 'd', 1),
+                ('c', 'e', 5),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_paths() {
+        let g = weighted();
+        assert_eq!(assert_ok!(g.bfs(&'a', &'e')), Some(vec!['a', 'c', 'e']));
+        assert_eq!(
+            assert_ok!(g.dijkstra(&'a', &'d')),
+            Some((vec!['a', 'b', 'd'], 2))
+        );
+        let r = assert_ok!(g.astar(&'e', &'a', |_| 0));
+        assert_eq!(r, Some((vec!['e', 'c', 'a'], 7)));
+        assert!(g.bfs(&'a', &'z').is_err());
+
+        let d = assert_ok!(g.distances(&'a'));
+        assert_eq!(d[&'e'], 7);
+        assert_eq!(d[&'a'], 0);
+    }
+
+    #[test]
+    fn test_shortest_paths() {
+        let g = Graph::from_weighted_edges(
+            false,
+            [('a', 'b', 1), ('b', 'd', 2), ('a', 'c', 2), ('c', 'd', 1)],
+        );
+        let r = assert_ok!(g.shortest_paths(&'a', &'d')).unwrap();
+        assert_eq!(r.cost, 3);
+        assert_eq!(r.nodes.len(), 4);
+        assert_eq!(r.edges.len(), 4);
+
+        let r = assert_ok!(weighted().shortest_paths(&'a', &'d')).unwrap();
+        assert_eq!(r.nodes, HashSet::from(['a', 'b', 'd']));
+        assert_eq!(r.edges, HashSet::from([('a', 'b'), ('b', 'd')]));
+
+        let mut g = weighted();
+        g.add_node('z');
+        assert!(assert_ok!(g.shortest_paths(&'a', &'z')).is_none());
+    }
+
+    #[test]
+    fn test_components() {
+        let g = Graph::from_edges(true, [(1, 2), (3, 2), (4, 5)]);
+        let c = g
+            .components()
+            .into_iter()
+            .map(|c| c.into_iter().sorted().collect_vec())
+            .sorted()
+            .collect_vec();
+        assert_eq!(c, vec![vec![1, 2, 3], vec![4, 5]]);
+    }
+
+    #[test]
+    fn test_topological_sort() {
+        let g = Graph::from_edges(
+            true,
+            [
+                ("shirt", "tie"),
+                ("tie", "jacket"),
+                ("shirt", "belt"),
+                ("belt", "jacket"),
+            ],
+        );
+        let order = assert_ok!(g.topological_sort());
+        let pos = |n| order.iter().position(|&m| m == n).unwrap();
+        assert!(pos("shirt") < pos("tie") && pos("tie") < pos("jacket"));
+        assert!(pos("belt") < pos("jacket"));
+        assert!(!g.has_cycle());
+
+        let g = Graph::from_edges(true, [(1, 2), (2, 3), (3, 1)]);
+        assert!(g.topological_sort().is_err());
+        assert!(g.has_cycle());
+    }
+
+    #[test]
+    fn test_undirected_cycles() {
+        let tree = Graph::from_edges(false, [(1, 2), (1, 3), (3, 4), (5, 6)]);
+        assert!(!tree.has_cycle());
+        let looped = Graph::from_edges(false, [(1, 2), (2, 3), (3, 1), (5, 6)]);
+        assert!(looped.has_cycle());
+    }
+
+    #[test]
+    fn test_max_clique() {
+        let g = Graph::from_edges(
+            false,
+            [
+                (1, 2),
+                (1, 3),
+                (2, 3),
+                (3, 4),
+                (4, 5),
+                (4, 6),
+                (4, 7),
+                (5, 6),
+                (5, 7),
+                (6, 7),
+            ],
+        );
+        let clique = assert_ok!(g.max_clique());
+        assert_eq!(clique.into_iter().sorted().collect_vec(), vec![4, 5, 6, 7]);
+        assert_eq!(assert_ok!(g.triangles()).len(), 5);
+
+        let directed = Graph::from_edges(true, [(1, 2), (2, 3), (3, 1)]);
+        assert!(directed.max_clique().is_err());
+        assert!(directed.triangles().is_err());
+    }
+
+    #[test]
+    fn test_grid() {
+        let grid = assert_ok!(Grid::chars("..#\n#..\n..."));
+        let g = Graph::from_grid(&grid, |&c| c == '.');
+        assert_eq!(g.len(), 7);
+        let (path, cost) = assert_ok!(g.dijkstra(&(0, 0), &(2, 1))).unwrap();
+        assert_eq!(cost, 3);
+        assert_eq!(path, vec![(0, 0), (1, 0), (1, 1), (2, 1)]);
+        assert!(g.has_cycle());
+    }
+}
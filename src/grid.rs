@@ -0,0 +1,470 @@
+//! A rectangular grid of cells, as found in about half of all puzzles.
+//!
+//! Cells are stored row by row in a single `Vec`, coordinates are `(x, y)` with `x` going right
+//! and `y` going down, starting at the top left corner.
+
+use std::{
+    fmt::{self, Display},
+    ops::{Index, IndexMut},
+};
+
+use color_eyre::eyre::{eyre, Result};
+
+use crate::{
+    geometry::{Coord, Point2},
+    parse::ParseError,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+/// Offsets of the four orthogonal neighbours: right, down, left, up
+const NEIGHBORS_4: [(isize, isize); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+/// Offsets of all eight neighbours, clockwise starting to the right
+const NEIGHBORS_8: [(isize, isize); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+impl<T> Grid<T> {
+    /// Builds a grid from its cells in row-major order
+    pub fn from_vec(width: usize, cells: Vec<T>) -> Result<Self> {
+        if !cells.len().is_multiple_of(width) {
+            return Err(eyre!(
+                "{} cells don't fit into rows of width {width}",
+                cells.len()
+            ));
+        }
+        let height = cells.len().checked_div(width).unwrap_or(0);
+        Ok(Self {
+            cells,
+            width,
+            height,
+        })
+    }
+
+    /// Parses a grid with one line per row, turning every character into a cell with `f`. A
+    /// character `f` has no cell for is an error.
+    pub fn parse(input: &str, mut f: impl FnMut(char) -> Option<T>) -> Result<Self, ParseError> {
+        let mut width = None;
+        let mut cells = Vec::with_capacity(input.len());
+
+        for (y, line) in input.lines().enumerate() {
+            let len = line.chars().count();
+            match width {
+                None => width = Some(len),
+                Some(w) if w != len => {
+                    let column = len.min(w) + 1;
+                    return Err(ParseError::in_line(
+                        y + 1,
+                        line,
+                        column,
+                        format!("a row of {w} cells"),
+                    ));
+                }
+                _ => (),
+            }
+            for (x, c) in line.chars().enumerate() {
+                let cell = f(c).ok_or_else(|| ParseError::in_line(y + 1, line, x + 1, "a cell"))?;
+                cells.push(cell);
+            }
+        }
+
+        let width = width.unwrap_or(0);
+        let height = cells.len().checked_div(width).unwrap_or(0);
+        Ok(Self {
+            cells,
+            width,
+            height,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn in_bounds(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    /// Turns signed coordinates into indices, `None` if they are outside the grid
+    pub fn checked_coord(&self, x: isize, y: isize) -> Option<(usize, usize)> {
+        let (x, y) = (usize::try_from(x).ok()?, usize::try_from(y).ok()?);
+        self.in_bounds(x, y).then_some((x, y))
+    }
+
+    fn index_of(&self, x: usize, y: usize) -> Option<usize> {
+        self.in_bounds(x, y).then(|| y * self.width + x)
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        self.index_of(x, y).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        self.index_of(x, y).map(|i| &mut self.cells[i])
+    }
+
+    /// Like [`Grid::get`], but negative coordinates are fine and simply outside the grid
+    pub fn get_signed(&self, x: isize, y: isize) -> Option<&T> {
+        self.checked_coord(x, y).and_then(|(x, y)| self.get(x, y))
+    }
+
+    pub fn get_signed_mut(&mut self, x: isize, y: isize) -> Option<&mut T> {
+        self.checked_coord(x, y)
+            .and_then(move |(x, y)| self.get_mut(x, y))
+    }
+
+    pub fn get_point<C: Coord>(&self, p: Point2<C>) -> Option<&T> {
+        p.to_grid().and_then(|(x, y)| self.get(x, y))
+    }
+
+    pub fn get_point_mut<C: Coord>(&mut self, p: Point2<C>) -> Option<&mut T> {
+        p.to_grid().and_then(move |(x, y)| self.get_mut(x, y))
+    }
+
+    /// All cells in row-major order
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// All cells in row-major order, together with their coordinates
+    pub fn cells(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, c)| ((i % width, i / width), c))
+    }
+
+    /// Coordinates of all cells matching `pred`, in row-major order
+    pub fn positions<'a>(
+        &'a self,
+        mut pred: impl FnMut(&T) -> bool + 'a,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        self.cells().filter_map(move |(p, c)| pred(c).then_some(p))
+    }
+
+    /// Coordinates of the first cell matching `pred`
+    pub fn find(&self, pred: impl FnMut(&T) -> bool) -> Option<(usize, usize)> {
+        self.positions(pred).next()
+    }
+
+    pub fn row(&self, y: usize) -> impl DoubleEndedIterator<Item = &T> {
+        let row = if y < self.height {
+            &self.cells[y * self.width..(y + 1) * self.width]
+        } else {
+            &[]
+        };
+        row.iter()
+    }
+
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> {
+        let cells = if x < self.width {
+            &self.cells[x..]
+        } else {
+            &[]
+        };
+        cells.iter().step_by(self.width.max(1))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = impl DoubleEndedIterator<Item = &T>> {
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl DoubleEndedIterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// The diagonals going down and to the right, starting with the bottom left corner
+    pub fn diagonals(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        let (w, h) = (self.width as isize, self.height as isize);
+        (0..(w + h - 1).max(0)).map(move |d| {
+            let (x, y) = if d < h {
+                (0, h - 1 - d)
+            } else {
+                (d - h + 1, 0)
+            };
+            self.ray(x, y, (1, 1))
+        })
+    }
+
+    /// The diagonals going down and to the left, starting with the top left corner
+    pub fn anti_diagonals(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        let (w, h) = (self.width as isize, self.height as isize);
+        (0..(w + h - 1).max(0)).map(move |d| {
+            let (x, y) = if d < w { (d, 0) } else { (w - 1, d - w + 1) };
+            self.ray(x, y, (-1, 1))
+        })
+    }
+
+    /// Cells starting at `(x, y)` and stepping by `(dx, dy)` until leaving the grid. A step of
+    /// `(0, 0)` never leaves, so it only yields the start cell.
+    pub fn ray(&self, x: isize, y: isize, (dx, dy): (isize, isize)) -> impl Iterator<Item = &T> {
+        let steps = if (dx, dy) == (0, 0) { 1 } else { usize::MAX };
+        (0..)
+            .take(steps)
+            .map(move |i| self.get_signed(x + i * dx, y + i * dy))
+            .take_while(Option::is_some)
+            .flatten()
+    }
+
+    /// The in-bounds orthogonal neighbours of `(x, y)`
+    pub fn neighbors4(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.offsets(x, y, &NEIGHBORS_4)
+    }
+
+    /// The in-bounds orthogonal and diagonal neighbours of `(x, y)`
+    pub fn neighbors8(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.offsets(x, y, &NEIGHBORS_8)
+    }
+
+    fn offsets<'a>(
+        &'a self,
+        x: usize,
+        y: usize,
+        offsets: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        offsets
+            .iter()
+            .filter_map(move |&(dx, dy)| self.checked_coord(x as isize + dx, y as isize + dy))
+    }
+
+    /// Builds a new grid of the same size by mapping every cell
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// A `width` x `height` grid with every cell set to `fill`
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Self {
+            cells: vec![fill; width * height],
+            width,
+            height,
+        }
+    }
+
+    /// Mirrors the grid along the diagonal from the top left corner, rows become columns
+    pub fn transpose(&self) -> Self {
+        self.remap(self.height, self.width, |x, y| (y, x))
+    }
+
+    /// Rotates the grid by 90° clockwise
+    pub fn rotate_right(&self) -> Self {
+        let h = self.height;
+        self.remap(h, self.width, |x, y| (y, h - 1 - x))
+    }
+
+    /// Rotates the grid by 90° counter-clockwise
+    pub fn rotate_left(&self) -> Self {
+        let w = self.width;
+        self.remap(self.height, w, |x, y| (w - 1 - y, x))
+    }
+
+    /// Mirrors the grid left to right
+    pub fn flip_horizontal(&self) -> Self {
+        let w = self.width;
+        self.remap(w, self.height, |x, y| (w - 1 - x, y))
+    }
+
+    /// Mirrors the grid top to bottom
+    pub fn flip_vertical(&self) -> Self {
+        let h = self.height;
+        self.remap(self.width, h, |x, y| (x, h - 1 - y))
+    }
+
+    /// Builds a `width` x `height` grid where the cell at `(x, y)` is taken from `source(x, y)`
+    fn remap(
+        &self,
+        width: usize,
+        height: usize,
+        source: impl Fn(usize, usize) -> (usize, usize),
+    ) -> Self {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let (sx, sy) = source(x, y);
+                self[(sx, sy)].clone()
+            })
+            .collect();
+        Self {
+            cells,
+            width,
+            height,
+        }
+    }
+}
+
+impl Grid<char> {
+    /// The plain character grid most puzzles start out with
+    pub fn chars(input: &str) -> Result<Self, ParseError> {
+        Self::parse(input, Some)
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
+        self.get(x, y).unwrap_or_else(|| {
+            panic!(
+                "({x}, {y}) is outside of the {}x{} grid",
+                self.width, self.height
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Self::Output {
+        let (w, h) = (self.width, self.height);
+        self.get_mut(x, y)
+            .unwrap_or_else(|| panic!("({x}, {y}) is outside of the {w}x{h} grid"))
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for c in row {
+                write!(f, "{c}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_ok::assert_ok;
+    use test_case::test_case;
+
+    const INPUT: &str = "abc
+def";
+
+    fn collect<'a>(rows: impl Iterator<Item = impl Iterator<Item = &'a char>>) -> Vec<String> {
+        rows.map(|r| r.collect()).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let g = assert_ok!(Grid::chars(INPUT));
+        assert_eq!((g.width(), g.height()), (3, 2));
+        assert_eq!(g[(2, 1)], 'f');
+        assert_eq!(g.to_string(), INPUT);
+
+        let g = assert_ok!(Grid::parse("12\n34", |c| c.to_digit(10)));
+        assert_eq!(g.iter().sum::<u32>(), 10);
+
+        let e = Grid::chars("abc\nde").unwrap_err();
+        assert_eq!((e.line, e.column), (2, 3));
+        let e = Grid::parse("12\n3x", |c| c.to_digit(10)).unwrap_err();
+        assert_eq!((e.line, e.column), (2, 2));
+    }
+
+    #[test]
+    fn test_empty() {
+        let g = assert_ok!(Grid::chars(""));
+        assert!(g.is_empty());
+        assert_eq!(g.diagonals().count(), 0);
+        assert_eq!(g.get(0, 0), None);
+    }
+
+    #[test_case(0, 0, Some('a'))]
+    #[test_case(2, 1, Some('f'))]
+    #[test_case(3, 0, None)]
+    #[test_case(0, 2, None)]
+    #[test_case(-1, 0, None)]
+    #[test_case(0, -1, None)]
+    fn test_get_signed(x: isize, y: isize, expected: Option<char>) {
+        let g = assert_ok!(Grid::chars(INPUT));
+        assert_eq!(g.get_signed(x, y).copied(), expected);
+    }
+
+    #[test]
+    fn test_get_point() {
+        let g = assert_ok!(Grid::chars(INPUT));
+        assert_eq!(g.get_point(Point2::new(1, 1)), Some(&'e'));
+        assert_eq!(g.get_point(Point2::new(-1, 1)), None);
+        assert_eq!(g.get_point(Point2::<i64>::new(1, 2)), None);
+    }
+
+    #[test]
+    fn test_lines() {
+        let g = assert_ok!(Grid::chars(INPUT));
+        assert_eq!(collect(g.rows()), vec!["abc", "def"]);
+        assert_eq!(collect(g.columns()), vec!["ad", "be", "cf"]);
+        assert_eq!(collect(g.diagonals()), vec!["d", "ae", "bf", "c"]);
+        assert_eq!(collect(g.anti_diagonals()), vec!["a", "bd", "ce", "f"]);
+        assert_eq!(g.row(1).rev().collect::<String>(), "fed");
+        assert_eq!(g.ray(2, 0, (-1, 1)).collect::<String>(), "ce");
+        assert_eq!(g.ray(1, 1, (0, 0)).collect::<String>(), "e");
+        assert_eq!(g.ray(3, 0, (0, 0)).count(), 0);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let g = Grid::new(3, 3, 0);
+        assert_eq!(g.neighbors4(1, 1).count(), 4);
+        assert_eq!(g.neighbors8(1, 1).count(), 8);
+        assert_eq!(g.neighbors4(0, 0).collect::<Vec<_>>(), vec![(1, 0), (0, 1)]);
+        assert_eq!(g.neighbors8(2, 2).count(), 3);
+    }
+
+    #[test]
+    fn test_transform() {
+        let g = assert_ok!(Grid::chars(INPUT));
+        assert_eq!(g.transpose().to_string(), "ad\nbe\ncf");
+        assert_eq!(g.rotate_right().to_string(), "da\neb\nfc");
+        assert_eq!(g.rotate_left().to_string(), "cf\nbe\nad");
+        assert_eq!(g.flip_horizontal().to_string(), "cba\nfed");
+        assert_eq!(g.flip_vertical().to_string(), "def\nabc");
+        assert_eq!(g.rotate_left().rotate_right(), g);
+        assert_eq!(
+            g.rotate_right().rotate_right(),
+            g.flip_horizontal().flip_vertical()
+        );
+    }
+
+    #[test]
+    fn test_positions() {
+        let g = assert_ok!(Grid::chars("a.a\n.a."));
+        assert_eq!(
+            g.positions(|&c| c == 'a').collect::<Vec<_>>(),
+            vec![(0, 0), (2, 0), (1, 1)]
+        );
+        assert_eq!(g.find(|&c| c == '.'), Some((1, 0)));
+    }
+}
@@ -0,0 +1,272 @@
+//! Sets of integers stored as disjoint half-open intervals.
+//!
+//! Touching intervals are merged on insert, so `[1, 3)` and `[3, 5)` become `[1, 5)`, and every
+//! value of the set lives in exactly one stored interval.
+
+use std::{collections::BTreeMap, iter, ops::Range};
+
+use crate::math::Integer;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    /// Interval start to (exclusive) end, never empty, never touching
+    map: BTreeMap<T, T>,
+}
+
+impl<T: Integer> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self {
+            map: BTreeMap::new(),
+        }
+    }
+
+    /// Adds every value of `range`, merging it with whatever it overlaps or touches
+    pub fn insert(&mut self, range: Range<T>) {
+        let Range { mut start, mut end } = range;
+        if start >= end {
+            return;
+        }
+        let touching = self
+            .map
+            .range(..=end)
+            .rev()
+            .take_while(|(_, &e)| e >= start)
+            .map(|(&s, _)| s)
+            .collect::<Vec<_>>();
+        for s in touching {
+            let e = self.map.remove(&s).expect("collected from the map");
+            start = start.min(s);
+            end = end.max(e);
+        }
+        self.map.insert(start, end);
+    }
+
+    /// Takes every value of `range` out, splitting intervals that stick out on either side
+    pub fn remove(&mut self, range: Range<T>) {
+        let Range { start, end } = range;
+        if start >= end {
+            return;
+        }
+        let overlapping = self
+            .map
+            .range(..end)
+            .rev()
+            .take_while(|(_, &e)| e > start)
+            .map(|(&s, &e)| (s, e))
+            .collect::<Vec<_>>();
+        for (s, e) in overlapping {
+            self.map.remove(&s);
+            if s < start {
+                self.map.insert(s, start);
+            }
+            if e > end {
+                self.map.insert(end, e);
+            }
+        }
+    }
+
+    /// Adds all intervals of `other`
+    pub fn merge(&mut self, other: &Self) {
+        for r in other.iter() {
+            self.insert(r);
+        }
+    }
+
+    /// Splits the set in two, returning everything from `at` on. An interval containing `at` is
+    /// cut in half.
+    pub fn split_off(&mut self, at: T) -> Self {
+        let mut right = self.map.split_off(&at);
+        if let Some(e) = self.map.values_mut().next_back().filter(|e| **e > at) {
+            right.insert(at, *e);
+            *e = at;
+        }
+        Self { map: right }
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.interval_of(value).is_some()
+    }
+
+    /// The stored interval `value` is in
+    pub fn interval_of(&self, value: T) -> Option<Range<T>> {
+        self.map
+            .range(..=value)
+            .next_back()
+            .filter(|(_, &e)| e > value)
+            .map(|(&s, &e)| s..e)
+    }
+
+    /// Whether any value of `range` is in the set
+    pub fn overlaps(&self, range: Range<T>) -> bool {
+        range.start < range.end
+            && self
+                .map
+                .range(..range.end)
+                .next_back()
+                .is_some_and(|(_, &e)| e > range.start)
+    }
+
+    /// The stored intervals in ascending order
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = Range<T>> + '_ {
+        self.map.iter().map(|(&s, &e)| s..e)
+    }
+
+    /// Number of stored intervals, not values
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Number of values in the set
+    pub fn total_len(&self) -> T {
+        self.map.iter().fold(T::ZERO, |acc, (&s, &e)| acc + (e - s))
+    }
+
+    /// The stretches of `within` that are not in the set, in ascending order
+    pub fn gaps(&self, within: Range<T>) -> impl Iterator<Item = Range<T>> + '_ {
+        let end = within.end;
+        let mut start = within.start.min(end);
+        if let Some(r) = self.interval_of(start) {
+            start = r.end.min(end);
+        }
+        self.map
+            .range(start..end)
+            .map(|(&s, &e)| (s, e))
+            .chain(iter::once((end, end)))
+            .scan(start, |cursor, (s, e)| {
+                let gap = *cursor..s;
+                *cursor = e;
+                Some(gap)
+            })
+            .filter(|gap| gap.start < gap.end)
+    }
+
+    /// Start of the first stretch of `len` values within `within` that is not in the set
+    pub fn first_free(&self, len: T, within: Range<T>) -> Option<T> {
+        self.gaps(within)
+            .find(|gap| gap.end - gap.start >= len)
+            .map(|gap| gap.start)
+    }
+}
+
+impl<T: Integer> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for r in iter {
+            set.insert(r);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use proptest::prelude::*;
+    use test_case::test_case;
+
+    fn set(ranges: &[Range<i32>]) -> IntervalSet<i32> {
+        ranges.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_insert_merges() {
+        let s = set(&[1..3, 5..7, 3..4, 10..12, 6..11]);
+        assert_eq!(s.iter().collect::<Vec<_>>(), vec![1..4, 5..12]);
+        assert_eq!(s.total_len(), 10);
+        assert!(s.contains(11));
+        assert!(!s.contains(4));
+        assert_eq!(s.interval_of(7), Some(5..12));
+    }
+
+    #[test]
+    fn test_remove_splits() {
+        let mut s = set(&[0..10, 20..30]);
+        s.remove(3..5);
+        s.remove(8..22);
+        s.remove(29..40);
+        assert_eq!(s.iter().collect::<Vec<_>>(), vec![0..3, 5..8, 22..29]);
+    }
+
+    #[test]
+    fn test_split_off() {
+        let mut s = set(&[0..10, 20..30]);
+        let right = s.split_off(5);
+        assert_eq!(s.iter().collect::<Vec<_>>(), vec![0..5]);
+        assert_eq!(right.iter().collect::<Vec<_>>(), vec![5..10, 20..30]);
+
+        s.merge(&right);
+        assert_eq!(s, set(&[0..10, 20..30]));
+    }
+
+    #[test_case(1, Some(3))]
+    #[test_case(2, Some(5))]
+    #[test_case(3, Some(10))]
+    #[test_case(9, None)]
+    fn test_first_free(len: i32, expected: Option<i32>) {
+        let s = set(&[0..3, 4..5, 7..10, 14..20]);
+        assert_eq!(s.first_free(len, 0..14), expected);
+    }
+
+    #[test]
+    fn test_gaps() {
+        let s = set(&[0..3, 4..5, 7..10]);
+        assert_eq!(s.gaps(1..12).collect::<Vec<_>>(), vec![3..4, 5..7, 10..12]);
+        assert_eq!(s.gaps(7..9).count(), 0);
+        assert!(s.overlaps(2..4));
+        assert!(!s.overlaps(5..7));
+    }
+
+    #[derive(Debug, Clone)]
+    enum Op {
+        Insert(Range<i32>),
+        Remove(Range<i32>),
+    }
+
+    fn op() -> impl Strategy<Value = Op> {
+        (any::<bool>(), 0i32..50, 0i32..10).prop_map(|(insert, s, l)| {
+            if insert {
+                Op::Insert(s..s + l)
+            } else {
+                Op::Remove(s..s + l)
+            }
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_matches_model(ops in proptest::collection::vec(op(), 0..30), len in 1i32..5) {
+            let mut s = IntervalSet::new();
+            let mut model = BTreeSet::new();
+            for op in ops {
+                match op {
+                    Op::Insert(r) => {
+                        model.extend(r.clone());
+                        s.insert(r);
+                    }
+                    Op::Remove(r) => {
+                        for v in r.clone() {
+                            model.remove(&v);
+                        }
+                        s.remove(r);
+                    }
+                }
+            }
+
+            for v in -1..61 {
+                prop_assert_eq!(s.contains(v), model.contains(&v));
+            }
+            prop_assert_eq!(s.total_len() as usize, model.len());
+            let stored: Vec<_> = s.iter().collect();
+            prop_assert!(stored.windows(2).all(|w| w[0].end < w[1].start));
+
+            let free = (0..60).find(|&v| (v..v + len).all(|v| v < 60 && !model.contains(&v)));
+            prop_assert_eq!(s.first_free(len, 0..60), free);
+        }
+    }
+}
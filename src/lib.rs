@@ -4,12 +4,22 @@
 #![feature(int_roundings)]
 #![feature(iterator_try_collect)]
 
+use std::{io::BufRead, str::FromStr};
+
 use color_eyre::eyre::{eyre, Error, Result};
 // use eyre::{anyhow, Error};
 
 use solutions::*;
 
+pub mod geometry;
+pub mod graph;
+pub mod grid;
+pub mod intervals;
+pub mod math;
+pub mod memo;
+pub mod parse;
 mod solutions;
+pub mod union_find;
 
 // should be done with macros or something
 const SOLVERS: &[&dyn Solver] = &[
@@ -18,16 +28,30 @@ const SOLVERS: &[&dyn Solver] = &[
     &Day25,
 ];
 
-pub fn run_solver(day: Day, part: Part, input: &str) -> Result<String> {
+/// Runs a single part of a day, `None` means the day has no such part
+pub fn run_solver(day: Day, part: Part, input: &str) -> Result<Option<String>> {
     // assert!(day < SOLVERS.len() && day > 0);
     // let day = day - 1;
 
     SOLVERS[day.index()].run_part(input, part)
 }
 
-pub enum ParsingErrors {
-    InvalidDay(String),
-    InvalidPart(String),
+/// Runs a named variant of a day instead of the plain puzzle answer
+pub fn run_variant(day: Day, part: Part, input: &str, variant: &str) -> Result<String> {
+    let variant = Variant::parse(variant)?;
+    SOLVERS[day.index()].run_variant(input, part, &variant)
+}
+
+/// Like [`run_variant`], but variants that can work line by line read `input` as they go instead
+/// of loading all of it
+pub fn run_variant_reader(
+    day: Day,
+    part: Part,
+    input: &mut dyn BufRead,
+    variant: &str,
+) -> Result<String> {
+    let variant = Variant::parse(variant)?;
+    SOLVERS[day.index()].run_variant_reader(input, part, &variant)
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -79,12 +103,106 @@ impl std::ops::Deref for Day {
 
 pub trait Solver {
     fn part_1(&self, input: &str) -> Result<String>;
-    fn part_2(&self, input: &str) -> Result<String>;
+    /// `None` for days with only a single part (looking at you, Christmas day)
+    fn part_2(&self, input: &str) -> Result<Option<String>>;
 
-    fn run_part(&self, input: &str, part: Part) -> Result<String> {
+    fn run_part(&self, input: &str, part: Part) -> Result<Option<String>> {
         match part {
-            Part::Part1 => self.part_1(input),
+            Part::Part1 => self.part_1(input).map(Some),
             Part::Part2 => self.part_2(input),
         }
     }
+
+    /// Names of the variants [`Solver::run_variant`] knows
+    fn variants(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Alternative ways to run a day: other parameters, other algorithms or more verbose output
+    fn run_variant(&self, input: &str, part: Part, variant: &Variant) -> Result<String> {
+        Err(unknown_variant(variant, self.variants()))
+    }
+
+    /// [`Solver::run_variant`] on input that still has to be read. Only days with variants that
+    /// don't need the whole input at once override this.
+    fn run_variant_reader(
+        &self,
+        input: &mut dyn BufRead,
+        part: Part,
+        variant: &Variant,
+    ) -> Result<String> {
+        self.run_variant(&read_input(input)?, part, variant)
+    }
+}
+
+pub(crate) fn read_input(input: &mut dyn BufRead) -> Result<String> {
+    let mut s = String::new();
+    input.read_to_string(&mut s)?;
+    Ok(s)
+}
+
+/// The error for a variant a day does not know, listing the ones it does
+pub(crate) fn unknown_variant(variant: &Variant, known: &[&str]) -> Error {
+    match known {
+        [] => eyre!("this day has no variants"),
+        known => eyre!(
+            "unknown variant {}, expected one of {}",
+            variant.name,
+            known.join(", ")
+        ),
+    }
+}
+
+/// A variant name with optional arguments, written as `name` or `name:key=value,key=value`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variant<'a> {
+    pub name: &'a str,
+    args: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Variant<'a> {
+    pub fn parse(s: &'a str) -> Result<Self> {
+        let (name, args) = s.split_once(':').unwrap_or((s, ""));
+        let args = args
+            .split(',')
+            .filter(|a| !a.is_empty())
+            .map(|a| {
+                a.split_once('=')
+                    .ok_or_else(|| eyre!("variant argument {a} is not key=value"))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { name, args })
+    }
+
+    /// The argument `key`, or `default` if it was not given
+    pub fn arg<T>(&self, key: &str, default: T) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        match self.args.iter().find(|(k, _)| *k == key) {
+            Some((_, v)) => v
+                .parse()
+                .map_err(|e| eyre!("invalid value {v} for variant argument {key}: {e}")),
+            None => Ok(default),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_ok::assert_ok;
+
+    #[test]
+    fn test_variant() {
+        let v = assert_ok!(Variant::parse("tolerate:k=2,max=4"));
+        assert_eq!(v.name, "tolerate");
+        assert_eq!(assert_ok!(v.arg("k", 1)), 2);
+        assert_eq!(assert_ok!(v.arg("min", 1)), 1);
+        assert!(v.arg::<u32>("max", 3).is_ok());
+        assert!(Variant::parse("x:k").is_err());
+        assert!(assert_ok!(Variant::parse("x:k=a")).arg("k", 0).is_err());
+        assert_eq!(assert_ok!(Variant::parse("plain")).name, "plain");
+    }
 }
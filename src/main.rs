@@ -1,6 +1,6 @@
-use std::path::PathBuf;
+use std::{fs::File, io::BufReader, path::PathBuf};
 
-use aoc2024::{run_solver, Day, Part};
+use aoc2024::{run_solver, run_variant_reader, Day, Part};
 use clap::Parser;
 
 #[derive(Parser, Debug)]
@@ -9,6 +9,9 @@ struct Cli {
     day: usize,
     part: usize,
     path: PathBuf,
+    /// Run a variant of the day instead, e.g. `tolerate:k=2`
+    #[arg(long)]
+    variant: Option<String>,
 }
 
 fn main() -> color_eyre::Result<()> {
@@ -23,14 +26,26 @@ fn main() -> color_eyre::Result<()> {
 
     println!("Day {} Part {}", *day, part as usize);
 
-    let input = std::fs::read_to_string(cli.path)?;
-    let inner = Instant::now();
+    // variants get to read the file themselves, some of them never hold all of it in memory
+    let (result, inner) = match &cli.variant {
+        Some(variant) => {
+            let mut input = BufReader::new(File::open(cli.path)?);
+            let inner = Instant::now();
+            let result = run_variant_reader(day, part, &mut input, variant)?;
+            (Some(result), inner.elapsed())
+        }
+        None => {
+            let input = std::fs::read_to_string(cli.path)?;
+            let inner = Instant::now();
+            (run_solver(day, part, &input)?, inner.elapsed())
+        }
+    };
 
-    let result = run_solver(day, part, &input)?;
-
-    let inner = inner.elapsed();
     let elapsed = total.elapsed();
-    println!("{}", result);
+    match result {
+        Some(result) => println!("{}", result),
+        None => println!("Day {} has no part {}", *day, part as usize),
+    }
     println!("solver: {:.2?}", inner);
     println!("Elapsed: {:.2?}", elapsed);
 
@@ -0,0 +1,474 @@
+//! Number theory and integer arithmetic helpers.
+//!
+//! Everything that can overflow goes through checked arithmetic and returns `None` instead of
+//! wrapping or panicking, so a too small integer type shows up as a missing answer.
+
+use std::{
+    fmt::{Debug, Display},
+    hash::Hash,
+    ops::{Add, Div, Mul, Neg, Rem, Sub},
+};
+
+/// The primitive integer types
+pub trait Integer:
+    Copy
+    + Debug
+    + Display
+    + Default
+    + Ord
+    + Hash
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const TEN: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+    fn checked_rem(self, rhs: Self) -> Option<Self>;
+    /// Absolute value, `None` for the one negative number that has no positive counterpart
+    fn checked_abs(self) -> Option<Self>;
+    fn rem_euclid(self, rhs: Self) -> Self;
+}
+
+/// The signed integer types, needed wherever Bézout coefficients show up
+pub trait Signed: Integer + Neg<Output = Self> {}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const TEN: Self = 10;
+
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_add(self, rhs)
+                }
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, rhs)
+                }
+                fn checked_mul(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, rhs)
+                }
+                fn checked_div(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_div(self, rhs)
+                }
+                fn checked_rem(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_rem(self, rhs)
+                }
+                #[allow(unused_comparisons)]
+                fn checked_abs(self) -> Option<Self> {
+                    if self < Self::ZERO {
+                        Self::ZERO.checked_sub(self)
+                    } else {
+                        Some(self)
+                    }
+                }
+                fn rem_euclid(self, rhs: Self) -> Self {
+                    <$t>::rem_euclid(self, rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl Signed for i8 {}
+impl Signed for i16 {}
+impl Signed for i32 {}
+impl Signed for i64 {}
+impl Signed for i128 {}
+impl Signed for isize {}
+
+/// Greatest common divisor, never negative. `gcd(0, 0)` is 0.
+///
+/// `None` only if the result doesn't fit, i.e. `gcd(MIN, 0)` or `gcd(MIN, MIN)`.
+pub fn gcd<T: Integer>(mut a: T, mut b: T) -> Option<T> {
+    while b != T::ZERO {
+        // only `MIN % -1` fails, and anything is divisible by -1
+        (a, b) = (b, a.checked_rem(b).unwrap_or(T::ZERO));
+    }
+    a.checked_abs()
+}
+
+/// Least common multiple, never negative. `lcm(0, x)` is 0.
+pub fn lcm<T: Integer>(a: T, b: T) -> Option<T> {
+    if a == T::ZERO || b == T::ZERO {
+        return Some(T::ZERO);
+    }
+    a.checked_div(gcd(a, b)?)?.checked_mul(b)?.checked_abs()
+}
+
+/// The extended Euclidean algorithm, returns `(g, x, y)` with `a * x + b * y == g`.
+///
+/// `g` has the sign that falls out of the algorithm, it is `gcd(a, b)` for non-negative inputs.
+/// `None` if a step overflows.
+pub fn extended_gcd<T: Signed>(a: T, b: T) -> Option<(T, T, T)> {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_s, mut s) = (T::ONE, T::ZERO);
+    let (mut old_t, mut t) = (T::ZERO, T::ONE);
+
+    // old - q * new, the update all three sequences share
+    let step = |old: T, new: T, q: T| old.checked_sub(q.checked_mul(new)?);
+    while r != T::ZERO {
+        // `MIN / -1` is the only division that fails, -1 divides anything so it is the gcd
+        let Some(q) = old_r.checked_div(r) else {
+            return Some((r, s, t));
+        };
+        let next = step(old_r, r, q)?;
+        if next == T::ZERO {
+            // the coefficients after this step are b / g and a / g, which might not fit
+            return Some((r, s, t));
+        }
+        (old_r, r) = (r, next);
+        (old_s, s) = (s, step(old_s, s, q)?);
+        (old_t, t) = (t, step(old_t, t, q)?);
+    }
+    Some((old_r, old_s, old_t))
+}
+
+/// `x` in `0..m` with `a * x ≡ 1 (mod m)`, `None` if `a` and `m` aren't coprime
+pub fn mod_inverse<T: Signed>(a: T, m: T) -> Option<T> {
+    if m <= T::ZERO {
+        return None;
+    }
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m)?;
+    (g == T::ONE).then(|| x.rem_euclid(m))
+}
+
+/// `a * b mod m` in `0..m` without overflowing on the way, `None` unless `m` is positive
+pub fn mul_mod<T: Integer>(a: T, b: T, m: T) -> Option<T> {
+    if m <= T::ZERO {
+        return None;
+    }
+    let (mut a, mut b) = (a.rem_euclid(m), b.rem_euclid(m));
+    if let Some(p) = a.checked_mul(b) {
+        return Some(p % m);
+    }
+    // double and add, subtracting before adding whenever the sum would reach `m`, so nothing
+    // ever gets bigger than `m`
+    let add_mod = |x: T, y: T| if x >= m - y { x - (m - y) } else { x + y };
+    let two = T::ONE + T::ONE;
+    let mut result = T::ZERO;
+    while b > T::ZERO {
+        if b % two == T::ONE {
+            result = add_mod(result, a);
+        }
+        a = add_mod(a, a);
+        b = b / two;
+    }
+    Some(result)
+}
+
+/// Chinese remainder theorem for `x ≡ r (mod m)` for every `(r, m)`, moduli don't need to be
+/// coprime.
+///
+/// Returns the smallest non-negative solution `x` together with the combined modulus, `None` if
+/// the congruences contradict each other or the combined modulus overflows.
+pub fn crt<T: Signed>(congruences: &[(T, T)]) -> Option<(T, T)> {
+    congruences
+        .iter()
+        .try_fold((T::ZERO, T::ONE), |(x, m), &(r, n)| {
+            if n <= T::ZERO {
+                return None;
+            }
+            let g = gcd(m, n)?;
+            let diff = r.checked_sub(x)?;
+            if diff % g != T::ZERO {
+                return None;
+            }
+            // x + m * k ≡ r (mod n)  <=>  (m / g) * k ≡ diff / g (mod n / g)
+            let n_g = n / g;
+            let k = mul_mod(diff / g, mod_inverse(m / g, n_g)?, n_g)?;
+            let l = m.checked_mul(n_g)?;
+            let x = x.checked_add(m.checked_mul(k)?)?.rem_euclid(l);
+            Some((x, l))
+        })
+}
+
+/// Number of decimal digits, the sign doesn't count. 0 has one digit.
+pub fn digits<T: Integer>(n: T) -> u32 {
+    let mut n = n;
+    let mut count = 1;
+    while n / T::TEN != T::ZERO {
+        n = n / T::TEN;
+        count += 1;
+    }
+    count
+}
+
+/// `10^exp`, `None` if it doesn't fit
+pub fn pow10<T: Integer>(exp: u32) -> Option<T> {
+    (0..exp).try_fold(T::ONE, |acc, _| acc.checked_mul(T::TEN))
+}
+
+/// Writes the digits of `b` after the ones of `a`, so `concat(12, 345) == Some(12345)`.
+/// `b` must not be negative.
+pub fn concat<T: Integer>(a: T, b: T) -> Option<T> {
+    if b < T::ZERO {
+        return None;
+    }
+    a.checked_mul(pow10(digits(b))?)?.checked_add(b)
+}
+
+/// Splits the decimal digits in two halves, `split_digits(1234) == Some((12, 34))`. `None` for
+/// negative numbers or an odd number of digits.
+pub fn split_digits<T: Integer>(n: T) -> Option<(T, T)> {
+    let d = digits(n);
+    if n < T::ZERO || !d.is_multiple_of(2) {
+        return None;
+    }
+    let p = pow10(d / 2)?;
+    Some((n / p, n % p))
+}
+
+/// Solves the square system `a * x = b` over the integers, `None` if `a` is singular, the
+/// solution isn't integral or something overflows on the way.
+///
+/// Uses fraction-free Gauss-Jordan elimination (Bareiss), every division in there is exact, so
+/// the intermediate values stay determinants of minors of `a`.
+pub fn solve_linear<T: Signed>(a: &[Vec<T>], b: &[T]) -> Option<Vec<T>> {
+    let n = b.len();
+    if a.len() != n || a.iter().any(|row| row.len() != n) {
+        return None;
+    }
+    let mut m: Vec<Vec<T>> = a
+        .iter()
+        .zip(b)
+        .map(|(row, &b)| row.iter().copied().chain([b]).collect())
+        .collect();
+
+    let mut prev = T::ONE;
+    for k in 0..n {
+        let pivot = (k..n).find(|&i| m[i][k] != T::ZERO)?;
+        m.swap(k, pivot);
+        for i in (0..n).filter(|&i| i != k) {
+            for j in (0..=n).filter(|&j| j != k) {
+                let v = m[i][j]
+                    .checked_mul(m[k][k])?
+                    .checked_sub(m[i][k].checked_mul(m[k][j])?)?;
+                m[i][j] = v.checked_div(prev)?;
+            }
+            m[i][k] = T::ZERO;
+        }
+        prev = m[k][k];
+    }
+
+    // every diagonal entry is now the determinant
+    m.iter()
+        .enumerate()
+        .map(|(i, row)| exact_div(row[n], row[i]))
+        .collect()
+}
+
+/// Solves `ax + by = e, cx + dy = f` by Cramer's rule, `None` unless there is exactly one integer
+/// solution
+pub fn solve_2x2<T: Signed>(a: T, b: T, c: T, d: T, e: T, f: T) -> Option<(T, T)> {
+    let det = a.checked_mul(d)?.checked_sub(b.checked_mul(c)?)?;
+    if det == T::ZERO {
+        return None;
+    }
+    let x = e.checked_mul(d)?.checked_sub(b.checked_mul(f)?)?;
+    let y = a.checked_mul(f)?.checked_sub(e.checked_mul(c)?)?;
+    Some((exact_div(x, det)?, exact_div(y, det)?))
+}
+
+/// `a / b` if it leaves no remainder and fits
+fn exact_div<T: Integer>(a: T, b: T) -> Option<T> {
+    let q = a.checked_div(b)?;
+    (a.checked_rem(b)? == T::ZERO).then_some(q)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use test_case::test_case;
+
+    #[test_case(12, 18, 6, 36)]
+    #[test_case(-4, 6, 2, 12)]
+    #[test_case(0, 5, 5, 0)]
+    #[test_case(0, 0, 0, 0)]
+    #[test_case(7, 13, 1, 91)]
+    fn test_gcd_lcm(a: i64, b: i64, g: i64, l: i64) {
+        assert_eq!(gcd(a, b), Some(g));
+        assert_eq!(lcm(a, b), Some(l));
+    }
+
+    #[test]
+    fn test_overflow() {
+        assert_eq!(gcd(i32::MIN, 0), None);
+        assert_eq!(lcm(u8::MAX, 254), None);
+        assert_eq!(concat(4294u32, 967295), Some(u32::MAX));
+        assert_eq!(concat(4294u32, 967296), None);
+        assert_eq!(pow10::<u64>(19), Some(10_000_000_000_000_000_000));
+        assert_eq!(pow10::<u64>(20), None);
+        assert_eq!(solve_2x2(i8::MAX, 1, 1, i8::MAX, 1, 1), None);
+        assert_eq!(gcd(i64::MIN, -1), Some(1));
+        assert_eq!(lcm(i64::MIN, -1), None);
+        assert_eq!(lcm(i64::MIN, 2), None);
+        assert_eq!(extended_gcd(i64::MAX, i64::MIN), Some((-1, 1, 1)));
+        assert_eq!(extended_gcd(i64::MIN, -1), Some((-1, 0, 1)));
+        assert_eq!(solve_2x2(-1, 0, 0, 1, i64::MIN, 0), None);
+        assert_eq!(solve_linear(&[vec![-1i64]], &[i64::MIN]), None);
+    }
+
+    #[test]
+    fn test_mul_mod() {
+        assert_eq!(mul_mod(1i64, 1, 0), None);
+        assert_eq!(mul_mod(1i64, 1, -5), None);
+        assert_eq!(mul_mod(-2i64, 3, 5), Some(4));
+        assert_eq!(mul_mod(u64::MAX - 1, u64::MAX - 1, u64::MAX), Some(1));
+        assert_eq!(mul_mod(i64::MIN, i64::MIN, i64::MAX), Some(1));
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(6, 9), None);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        // not coprime, but compatible
+        assert_eq!(crt(&[(3, 4), (5, 6)]), Some((11, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt::<i64>(&[]), Some((0, 1)));
+    }
+
+    #[test_case(0, 1)]
+    #[test_case(9, 1)]
+    #[test_case(10, 2)]
+    #[test_case(-12345, 5)]
+    #[test_case(i64::MAX, 19)]
+    fn test_digits(n: i64, d: u32) {
+        assert_eq!(digits(n), d);
+    }
+
+    #[test]
+    fn test_concat_split() {
+        assert_eq!(concat(12, 345), Some(12345));
+        assert_eq!(concat(15u64, 0), Some(150));
+        assert_eq!(split_digits(1234), Some((12, 34)));
+        assert_eq!(split_digits(1000), Some((10, 0)));
+        assert_eq!(split_digits(123), None);
+    }
+
+    #[test]
+    fn test_solve() {
+        // the first claw machine of 2024 day 13
+        assert_eq!(solve_2x2(94, 22, 34, 67, 8400, 5400), Some((80, 40)));
+        assert_eq!(solve_2x2(26, 67, 66, 21, 12748, 12176), None);
+
+        let a = vec![vec![2, 1, -1], vec![-3, -1, 2], vec![-2, 1, 2]];
+        assert_eq!(solve_linear(&a, &[8, -11, -3]), Some(vec![2, 3, -1]));
+        let singular = vec![vec![1, 2], vec![2, 4]];
+        assert_eq!(solve_linear(&singular, &[3, 6]), None);
+        // needs a row swap for the first pivot
+        let a = vec![vec![0, 1], vec![1, 0]];
+        assert_eq!(solve_linear(&a, &[5, 7]), Some(vec![7, 5]));
+    }
+
+    fn check_bezout(a: i64, b: i64) -> Result<(), TestCaseError> {
+        let (g, x, y) = extended_gcd(a, b).unwrap();
+        let (a, b) = (a as i128, b as i128);
+        prop_assert_eq!(a * x as i128 + b * y as i128, g as i128);
+        prop_assert_eq!(g.checked_abs(), gcd(a as i64, b as i64));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bezout_extremes() {
+        let extremes = [i64::MIN, i64::MIN + 1, -1, 0, 1, i64::MAX - 1, i64::MAX];
+        for a in extremes {
+            for b in extremes {
+                check_bezout(a, b).unwrap();
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn prop_gcd_divides(a in -10_000i64..10_000, b in -10_000i64..10_000) {
+            let g = gcd(a, b).unwrap();
+            prop_assert!(g >= 0);
+            if g != 0 {
+                prop_assert_eq!(a % g, 0);
+                prop_assert_eq!(b % g, 0);
+                prop_assert_eq!(gcd(a / g, b / g), Some(1));
+            }
+        }
+
+        #[test]
+        fn prop_lcm(a in 1u64..100_000, b in 1u64..100_000) {
+            let l = lcm(a, b).unwrap();
+            prop_assert_eq!(l % a, 0);
+            prop_assert_eq!(l % b, 0);
+            prop_assert_eq!(l * gcd(a, b).unwrap(), a * b);
+        }
+
+        #[test]
+        fn prop_bezout(a in any::<i64>(), b in any::<i64>()) {
+            check_bezout(a, b)?;
+        }
+
+        #[test]
+        fn prop_mod_inverse(a in any::<i64>(), m in 1i64..1_000_000_007) {
+            match mod_inverse(a, m) {
+                Some(x) => prop_assert_eq!(mul_mod(a, x, m), Some(1 % m)),
+                None => prop_assert_ne!(gcd(a.rem_euclid(m), m), Some(1)),
+            }
+        }
+
+        #[test]
+        fn prop_mul_mod(a in any::<u64>(), b in any::<u64>(), m in 1u64..) {
+            let expected = (a as u128 * b as u128 % m as u128) as u64;
+            prop_assert_eq!(mul_mod(a, b, m), Some(expected));
+        }
+
+        #[test]
+        fn prop_crt(x in 0i64..1_000_000, m in proptest::collection::vec(1i64..1000, 1..5)) {
+            let congruences: Vec<_> = m.iter().map(|&m| (x % m, m)).collect();
+            let (y, l) = crt(&congruences).unwrap();
+            prop_assert_eq!(l, m.iter().try_fold(1, |acc, &m| lcm(acc, m)).unwrap());
+            prop_assert_eq!(y, x % l);
+        }
+
+        #[test]
+        fn prop_concat(a in 0u64..1_000_000, b in 0u64..1_000_000) {
+            let c = concat(a, b).unwrap();
+            prop_assert_eq!(c.to_string(), format!("{a}{b}"));
+            if a != 0 {
+                prop_assert_eq!(digits(c), digits(a) + digits(b));
+            }
+        }
+
+        #[test]
+        fn prop_solve_linear(
+            a in proptest::collection::vec(-20i64..20, 9),
+            x in proptest::collection::vec(-100i64..100, 3),
+        ) {
+            let a: Vec<Vec<_>> = a.chunks(3).map(|r| r.to_vec()).collect();
+            let b: Vec<_> = a.iter().map(|r| r.iter().zip(&x).map(|(a, x)| a * x).sum()).collect();
+            let det = a[0][0] * (a[1][1] * a[2][2] - a[1][2] * a[2][1])
+                - a[0][1] * (a[1][0] * a[2][2] - a[1][2] * a[2][0])
+                + a[0][2] * (a[1][0] * a[2][1] - a[1][1] * a[2][0]);
+            let r = solve_linear(&a, &b);
+            if det == 0 {
+                prop_assert_eq!(r, None);
+            } else {
+                prop_assert_eq!(r, Some(x));
+            }
+        }
+    }
+}
@@ -0,0 +1,216 @@
+//! Memoization for recursive solvers.
+//!
+//! [`Memo`] is a cache that hands itself back to the computation on a miss, so recursive
+//! functions just take `&mut Memo` and go through [`Memo::get_or_insert_with`]:
+//!
+//! ```
+//! use aoc2024::memo::Memo;
+//!
+//! fn fib(n: u64, memo: &mut Memo<u64, u64>) -> u64 {
+//!     memo.get_or_insert_with(n, |memo| match n {
+//!         0 | 1 => n,
+//!         _ => fib(n - 1, memo) + fib(n - 2, memo),
+//!     })
+//! }
+//!
+//! let mut memo = Memo::new();
+//! assert_eq!(fib(90, &mut memo), 2880067194370816120);
+//! assert_eq!(memo.stats().misses, 91);
+//! ```
+
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::{BuildHasher, Hash},
+};
+
+/// The hasher used by rustc: not DoS resistant, but much faster than SipHash for the small keys
+/// puzzles use
+pub use rustc_hash::FxBuildHasher;
+
+/// How well a [`Memo`] did
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub hits: usize,
+    pub misses: usize,
+    pub entries: usize,
+}
+
+impl Stats {
+    /// Fraction of lookups answered from the cache, 0 if there were none
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            total => self.hits as f64 / total as f64,
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} hits, {} misses ({:.1}% hit rate), {} entries",
+            self.hits,
+            self.misses,
+            self.hit_rate() * 100.0,
+            self.entries
+        )
+    }
+}
+
+/// A cache of already computed results, hashing keys with `S`
+#[derive(Debug, Clone)]
+pub struct Memo<K, V, S = FxBuildHasher> {
+    cache: HashMap<K, V, S>,
+    hits: usize,
+    misses: usize,
+}
+
+impl<K: Hash + Eq, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<K: Hash + Eq, V: Clone, S: BuildHasher + Default> Default for Memo<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K: Hash + Eq, V: Clone, S: BuildHasher> Memo<K, V, S> {
+    /// A memo using a different hasher, e.g. `RandomState` for keys that collide a lot with Fx
+    pub fn with_hasher(hasher: S) -> Self {
+        Self {
+            cache: HashMap::with_hasher(hasher),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns the cached value for `key`, or computes it with `f` and remembers it.
+    ///
+    /// `f` gets the memo back, so it can recurse. A key that is still being computed further up
+    /// the call stack is not cached yet and will be computed again, so make sure the recursion
+    /// terminates on its own.
+    pub fn get_or_insert_with(&mut self, key: K, f: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(v) = self.cache.get(&key) {
+            self.hits += 1;
+            return v.clone();
+        }
+        self.misses += 1;
+        let v = f(self);
+        self.cache.insert(key, v.clone());
+        v
+    }
+
+    /// The cached value for `key`, without counting towards the statistics
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.cache.get(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// Forgets all cached values and resets the statistics
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.hits = 0;
+        self.misses = 0;
+    }
+
+    pub fn stats(&self) -> Stats {
+        Stats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.cache.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::hash_map::RandomState;
+
+    use super::*;
+
+    /// Number of ways to build `design` out of `towels`, as in 2024 day 19
+    fn arrangements<'a, S: BuildHasher>(
+        design: &'a str,
+        towels: &[&str],
+        memo: &mut Memo<&'a str, u64, S>,
+    ) -> u64 {
+        if design.is_empty() {
+            return 1;
+        }
+        memo.get_or_insert_with(design, |memo| {
+            towels
+                .iter()
+                .filter_map(|t| design.strip_prefix(t))
+                .map(|rest| arrangements(rest, towels, memo))
+                .sum()
+        })
+    }
+
+    const TOWELS: [&str; 8] = ["r", "wr", "b", "g", "bwu", "rb", "gb", "br"];
+
+    #[test]
+    fn test_arrangements() {
+        let mut memo = Memo::new();
+        let counts: Vec<_> = [
+            "brwrr", "bggr", "gbbr", "rrbgbr", "ubwu", "bwurrg", "brgr", "bbrgwb",
+        ]
+        .into_iter()
+        .map(|d| arrangements(d, &TOWELS, &mut memo))
+        .collect();
+        assert_eq!(counts, vec![2, 1, 4, 6, 0, 1, 2, 0]);
+        assert!(memo.stats().hits > 0);
+    }
+
+    #[test]
+    fn test_hasher_is_configurable() {
+        let mut fx = Memo::new();
+        let mut sip: Memo<_, _, RandomState> = Memo::default();
+        let design = "rrbgbrrrbgbrrrbgbr";
+        assert_eq!(
+            arrangements(design, &TOWELS, &mut fx),
+            arrangements(design, &TOWELS, &mut sip)
+        );
+        assert_eq!(fx.stats(), sip.stats());
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut memo: Memo<u32, u32> = Memo::new();
+        assert_eq!(memo.stats().hit_rate(), 0.0);
+        memo.get_or_insert_with(1, |_| 10);
+        memo.get_or_insert_with(1, |_| unreachable!());
+        memo.get_or_insert_with(1, |_| unreachable!());
+        memo.get_or_insert_with(2, |_| 20);
+
+        let stats = memo.stats();
+        assert_eq!(
+            stats,
+            Stats {
+                hits: 2,
+                misses: 2,
+                entries: 2
+            }
+        );
+        assert_eq!(
+            stats.to_string(),
+            "2 hits, 2 misses (50.0% hit rate), 2 entries"
+        );
+        assert_eq!(memo.get(&2), Some(&20));
+
+        memo.clear();
+        assert!(memo.is_empty());
+        assert_eq!(memo.stats(), Stats::default());
+    }
+}
@@ -0,0 +1,312 @@
+//! Small nom combinators for the usual puzzle input shapes.
+//!
+//! Everything parses `&str` with nom's default error type, [`finish`] runs a parser over a whole
+//! input and turns failures into a [`ParseError`] pointing at the offending line and column.
+
+use std::{fmt, str::FromStr};
+
+use color_eyre::eyre::Result;
+use nom::{
+    bytes::complete::{tag, take, take_while1},
+    character::complete::{digit1, line_ending, multispace0, one_of, space0, space1},
+    combinator::{eof, map_res, not, opt, recognize},
+    error::{self, Error, ErrorKind},
+    multi::{many0, many1, separated_list1},
+    sequence::{pair, preceded, separated_pair, terminated, tuple},
+    IResult, InputIter, InputLength, InputTake, Parser,
+};
+
+use crate::grid::Grid;
+
+/// An unsigned integer, no sign allowed
+pub fn unsigned<T: FromStr>(input: &str) -> IResult<&str, T> {
+    map_res(digit1, str::parse)(input)
+}
+
+/// An integer with an optional leading `+` or `-`
+pub fn signed<T: FromStr>(input: &str) -> IResult<&str, T> {
+    map_res(recognize(preceded(opt(one_of("+-")), digit1)), str::parse)(input)
+}
+
+/// Integers on a single line, separated by spaces or tabs
+pub fn numbers<T: FromStr>(input: &str) -> IResult<&str, Vec<T>> {
+    separated_list1(space1, signed)(input)
+}
+
+/// A name made of letters, digits and underscores
+pub fn ident(input: &str) -> IResult<&str, &str> {
+    take_while1(|c: char| c.is_alphanumeric() || c == '_')(input)
+}
+
+/// One `parser` per line
+pub fn lines<'a, O>(
+    parser: impl Parser<&'a str, O, Error<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    separated_list1(line_ending, parser)
+}
+
+/// Blocks separated by a blank line, each handed to `parser` as a whole
+pub fn blocks<'a, O>(
+    parser: impl Parser<&'a str, O, Error<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>> {
+    separated_list1(pair(line_ending, line_ending), parser)
+}
+
+/// A `key: value` pair, any amount of spaces after the colon
+pub fn key_value<'a, K, V>(
+    key: impl Parser<&'a str, K, Error<&'a str>>,
+    value: impl Parser<&'a str, V, Error<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, (K, V)> {
+    separated_pair(key, pair(tag(":"), space0), value)
+}
+
+/// A rectangular grid, one row per line and one `cell` per character (or whatever `cell` eats).
+///
+/// A row that is shorter or longer than the first one fails right where it stops matching up.
+pub fn grid<'a, T>(
+    mut cell: impl Parser<&'a str, T, Error<&'a str>>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Grid<T>> {
+    move |input: &'a str| {
+        let (mut rest, mut cells) = many1(|i| cell.parse(i))(input)?;
+        let width = cells.len();
+        while let Ok((mut pos, _)) = line_ending::<_, Error<&str>>(rest) {
+            let mut len = 0;
+            loop {
+                match cell.parse(pos) {
+                    Ok((next, _)) if next.len() == pos.len() => break,
+                    Ok(_) if len == width => {
+                        return Err(nom::Err::Failure(Error::new(pos, ErrorKind::LengthValue)))
+                    }
+                    Ok((next, c)) => {
+                        cells.push(c);
+                        len += 1;
+                        pos = next;
+                    }
+                    Err(nom::Err::Error(_)) => break,
+                    Err(e) => return Err(e),
+                }
+            }
+            match len {
+                // like `lines`, a line ending that isn't followed by another row is left over
+                0 => break,
+                len if len < width => {
+                    return Err(nom::Err::Failure(Error::new(pos, ErrorKind::LengthValue)))
+                }
+                _ => rest = pos,
+            }
+        }
+        let grid = Grid::from_vec(width, cells).expect("all rows have the same width");
+        Ok((rest, grid))
+    }
+}
+
+/// Skips ahead one character at a time until `parser` matches, then returns its result
+pub fn skip_until<'a, I, O, E, F>(mut parser: F) -> impl FnMut(I) -> IResult<I, O, E> + 'a
+where
+    I: Clone + InputLength + InputIter + InputTake + 'a,
+    F: Parser<I, O, E> + 'a,
+    E: error::ParseError<I> + 'a,
+{
+    move |input: I| {
+        let (input, _) = many0(preceded(not(|i| parser.parse(i)), take(1u8)))(input)?;
+        parser.parse(input)
+    }
+}
+
+/// Runs `parser` over all of `input`, only trailing whitespace may be left over
+pub fn finish<'a, O>(input: &'a str, parser: impl Parser<&'a str, O, Error<&'a str>>) -> Result<O> {
+    terminated(parser, tuple((multispace0, eof)))(input)
+        .map(|(_, o)| o)
+        .map_err(|e| ParseError::from_nom(input, e).into())
+}
+
+/// Where and why parsing the puzzle input went wrong.
+///
+/// Renders like a compiler diagnostic, with the offending line and a caret under the column:
+///
+/// ```text
+/// failed to parse line 2, column 3: expected a number
+///   |
+/// 2 | 3 x
+///   |   ^
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column, counted in characters
+    pub column: usize,
+    /// The whole offending line
+    pub snippet: String,
+    pub expected: String,
+}
+
+impl ParseError {
+    /// An error in `line`, which is line number `line_no` (1-based) of the input. A `column` of 0
+    /// is taken to mean the start of the line.
+    pub fn in_line(line_no: usize, line: &str, column: usize, expected: impl Into<String>) -> Self {
+        Self {
+            line: line_no,
+            column: column.max(1),
+            snippet: line.to_string(),
+            expected: expected.into(),
+        }
+    }
+
+    /// An error at the start of `rest`, which has to be a suffix of `input`
+    pub fn at(input: &str, rest: &str, expected: impl Into<String>) -> Self {
+        let offset = input.len() - rest.len();
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = input[offset..]
+            .find('\n')
+            .map(|i| offset + i)
+            .unwrap_or(input.len());
+
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: input[line_start..offset].chars().count() + 1,
+            snippet: input[line_start..line_end]
+                .trim_end_matches('\r')
+                .to_string(),
+            expected: expected.into(),
+        }
+    }
+
+    /// Locates a nom failure within the `input` it was parsing
+    pub fn from_nom(input: &str, err: nom::Err<Error<&str>>) -> Self {
+        match err {
+            nom::Err::Error(e) | nom::Err::Failure(e) => Self::at(input, e.input, describe(e.code)),
+            nom::Err::Incomplete(_) => Self::at(input, "", "more input"),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let gutter = " ".repeat(self.line.to_string().len());
+        writeln!(
+            f,
+            "failed to parse line {}, column {}: expected {}",
+            self.line, self.column, self.expected
+        )?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{} | {}", self.line, self.snippet)?;
+        write!(
+            f,
+            "{gutter} | {}^",
+            " ".repeat(self.column.saturating_sub(1))
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn describe(kind: ErrorKind) -> &'static str {
+    match kind {
+        ErrorKind::Digit | ErrorKind::MapRes => "a number",
+        ErrorKind::Space | ErrorKind::MultiSpace => "whitespace",
+        ErrorKind::Tag => "a different token",
+        ErrorKind::CrLf => "a line break",
+        ErrorKind::Eof => "the end of the input",
+        ErrorKind::OneOf => "a different character",
+        // only produced by `grid`
+        ErrorKind::LengthValue => "a row as long as the first one",
+        _ => "valid input",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_ok::assert_ok;
+    use test_case::test_case;
+
+    #[test_case("42", 42)]
+    #[test_case("-7", -7)]
+    #[test_case("+13", 13)]
+    fn test_signed(input: &str, expected: i64) {
+        let r = assert_ok!(finish(input, signed::<i64>));
+        assert_eq!(expected, r);
+    }
+
+    #[test]
+    fn test_unsigned() {
+        assert_eq!(assert_ok!(unsigned::<u8>("255,")), (",", 255));
+        assert!(unsigned::<u8>("256").is_err());
+        assert!(unsigned::<u32>("-1").is_err());
+    }
+
+    #[test]
+    fn test_numbers() {
+        let r = assert_ok!(finish("1 2\t-3   4\n", numbers::<i32>));
+        assert_eq!(r, vec![1, 2, -3, 4]);
+        let r = assert_ok!(finish("1 2\n3 4", lines(numbers::<i32>)));
+        assert_eq!(r, vec![vec![1, 2], vec![3, 4]]);
+    }
+
+    #[test]
+    fn test_blocks() {
+        let r = assert_ok!(finish("1\n2\n\n3\n\n4\n5\n", blocks(lines(unsigned::<u8>))));
+        assert_eq!(r, vec![vec![1, 2], vec![3], vec![4, 5]]);
+    }
+
+    #[test]
+    fn test_key_value() {
+        let r = assert_ok!(finish(
+            "x00: 1\ny01:0",
+            lines(key_value(ident, unsigned::<u8>))
+        ));
+        assert_eq!(r, vec![("x00", 1), ("y01", 0)]);
+    }
+
+    #[test]
+    fn test_grid() {
+        let g = assert_ok!(finish("#.\n.#\n", grid(one_of(".#"))));
+        assert_eq!(g.to_string(), "#.\n.#");
+        let e = parse_error(finish("#.\n.", grid(one_of(".#"))).unwrap_err());
+        assert_eq!((e.line, e.column), (2, 2));
+        assert_eq!(e.expected, "a row as long as the first one");
+        let e = parse_error(finish("#.\n.#\n#..", grid(one_of(".#"))).unwrap_err());
+        assert_eq!((e.line, e.column, e.snippet.as_str()), (3, 3, "#.."));
+        let e = parse_error(finish("#.\n.x", grid(one_of(".#"))).unwrap_err());
+        assert_eq!((e.line, e.column), (2, 2));
+    }
+
+    #[test]
+    fn test_skip_until() {
+        let r = assert_ok!(many0(skip_until(preceded(tag("n="), unsigned::<u32>)))(
+            "abc n=1 n=x n=23 zz"
+        ));
+        assert_eq!(r.1, vec![1, 23]);
+    }
+
+    fn parse_error(e: color_eyre::Report) -> ParseError {
+        assert_ok!(e.downcast::<ParseError>())
+    }
+
+    #[test]
+    fn test_error_location() {
+        let e = parse_error(finish("1 2\n3 x\n", lines(numbers::<i32>)).unwrap_err());
+        assert_eq!((e.line, e.column), (2, 3));
+        assert_eq!(e.snippet, "3 x");
+        let e = parse_error(finish("12\n34\n5a", lines(unsigned::<u8>)).unwrap_err());
+        assert_eq!((e.line, e.column, e.snippet.as_str()), (3, 2, "5a"));
+        let e = parse_error(finish("", unsigned::<u8>).unwrap_err());
+        assert_eq!((e.line, e.column, e.expected.as_str()), (1, 1, "a number"));
+    }
+
+    #[test]
+    fn test_error_render() {
+        let e = ParseError::in_line(12, "1 2 x", 5, "a number");
+        assert_eq!(
+            e.to_string(),
+            "failed to parse line 12, column 5: expected a number
+   |
+12 | 1 2 x
+   |     ^"
+        );
+        let e = ParseError::in_line(1, "x", 0, "a number");
+        assert!(e.to_string().ends_with("1 | x\n  | ^"));
+    }
+}
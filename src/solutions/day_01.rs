@@ -1,25 +1,57 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, io::BufRead};
 
-use crate::Solver;
-use color_eyre::eyre::Result;
+use crate::{parse::ParseError, read_input, unknown_variant, Part, Solver, Variant};
+use color_eyre::eyre::{eyre, Result};
 use itertools::{multiunzip, Itertools, MultiUnzip};
+use rustc_hash::FxHashMap;
 
 pub struct Day;
 
 impl Solver for Day {
     fn part_1(&self, input: &str) -> Result<String> {
-        let z = solve_1(input);
+        let z = solve_1(input)?;
         Ok(z.to_string())
     }
 
-    fn part_2(&self, input: &str) -> Result<String> {
-        let z = solve_2(input);
-        Ok(z.to_string())
+    fn part_2(&self, input: &str) -> Result<Option<String>> {
+        let z = solve_2(input)?;
+        Ok(Some(z.to_string()))
+    }
+
+    fn variants(&self) -> &'static [&'static str] {
+        &["streaming", "metrics"]
+    }
+
+    /// `streaming` solves part 1 with a radix sort and part 2 from per-value counts, for inputs
+    /// with tens of millions of rows. `metrics:top=5` compares the two lists in a few more ways
+    /// and breaks the similarity score down per value.
+    fn run_variant(&self, input: &str, part: Part, variant: &Variant) -> Result<String> {
+        match variant.name {
+            "streaming" => Day::solve_streaming(input.as_bytes(), part),
+            "metrics" => {
+                let counts = Counts::from_pairs(Day::pairs(input))?;
+                counts.report(variant.arg("top", 5)?)
+            }
+            _ => Err(unknown_variant(variant, self.variants())),
+        }
+    }
+
+    /// `streaming` reads the input one line at a time, so it never has to fit into memory
+    fn run_variant_reader(
+        &self,
+        input: &mut dyn BufRead,
+        part: Part,
+        variant: &Variant,
+    ) -> Result<String> {
+        match variant.name {
+            "streaming" => Day::solve_streaming(input, part),
+            _ => self.run_variant(&read_input(input)?, part, variant),
+        }
     }
 }
 
-fn solve_1(input: &str) -> i32 {
-    let (a, b) = Day::parse_input(input);
+fn solve_1(input: &str) -> Result<i32> {
+    let (a, b) = Day::parse_input(input)?;
     let a = a.iter().sorted();
     let b = b.iter().sorted();
 
@@ -27,10 +59,10 @@ fn solve_1(input: &str) -> i32 {
     let y = 5i32;
 
     let z = a.zip(b).fold(0, |acc, (&a, &b)| acc + a.abs_diff(b)) as i32;
-    z
+    Ok(z)
 }
-fn solve_2(input: &str) -> i32 {
-    let (a, b) = Day::parse_input(input);
+fn solve_2(input: &str) -> Result<i32> {
+    let (a, b) = Day::parse_input(input)?;
     let mut table = HashMap::new();
     for &num in b.iter() {
         if let Some(&val) = table.get(&num) {
@@ -47,24 +79,250 @@ fn solve_2(input: &str) -> i32 {
         };
     }
 
-    result
+    Ok(result)
 }
 
 impl Day {
-    fn parse_input(input: &str) -> (Vec<i32>, Vec<i32>) {
-        let v: Vec<(i32, i32)> = input
+    fn parse_input(input: &str) -> Result<(Vec<i32>, Vec<i32>), ParseError> {
+        let v: Vec<(i32, i32)> = Self::pairs(input).collect::<Result<_, _>>()?;
+        Ok(multiunzip(v))
+    }
+
+    /// Solves either part reading one line of `reader` at a time. Part 1 keeps both lists as
+    /// 4 bytes per row, part 2 only a count per distinct value.
+    fn solve_streaming(reader: impl BufRead, part: Part) -> Result<String> {
+        let pairs = Self::read_pairs(reader);
+        Ok(match part {
+            Part::Part1 => total_distance(pairs)?.to_string(),
+            Part::Part2 => Counts::from_pairs(pairs)?.similarity()?.to_string(),
+        })
+    }
+
+    /// Like [`Day::pairs`], but reads the lines from `reader` as they are needed
+    fn read_pairs(reader: impl BufRead) -> impl Iterator<Item = Result<(i32, i32)>> {
+        reader
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| match line {
+                Ok(l) if l.trim().is_empty() => None,
+                Ok(l) => Some(Self::parse_line(i + 1, &l).map_err(Into::into)),
+                Err(e) => Some(Err(e.into())),
+            })
+    }
+
+    /// The number pairs one line at a time, skipping blank lines
+    fn pairs(input: &str) -> impl Iterator<Item = Result<(i32, i32), ParseError>> + '_ {
+        input
             .lines()
-            .filter_map(|s| s.split_once("   "))
-            .map(|(a, b)| (a.parse().unwrap(), b.parse().unwrap()))
-            .collect();
-        let v = multiunzip(v);
-        v
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty())
+            .map(|(i, l)| Self::parse_line(i + 1, l))
+    }
+
+    /// Two numbers separated by any amount of whitespace
+    fn parse_line(line_no: usize, line: &str) -> Result<(i32, i32), ParseError> {
+        let number = |(column, field): (usize, &str)| {
+            field
+                .parse()
+                .map_err(|_| ParseError::in_line(line_no, line, column, "a number"))
+        };
+        let mut fields = fields(line);
+        match (fields.next(), fields.next(), fields.next()) {
+            (Some(a), Some(b), None) => Ok((number(a)?, number(b)?)),
+            (_, _, Some((column, _))) => Err(ParseError::in_line(
+                line_no,
+                line,
+                column,
+                "the end of the line",
+            )),
+            _ => Err(ParseError::in_line(
+                line_no,
+                line,
+                line.trim_end().chars().count() + 1,
+                "two numbers",
+            )),
+        }
+    }
+}
+
+/// The non-empty fields of `line` with their 1-based columns
+fn fields(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split(char::is_whitespace)
+        .scan(1, |column, field| {
+            let start = *column;
+            *column += field.chars().count() + 1;
+            Some((start, field))
+        })
+        .filter(|(_, field)| !field.is_empty())
+}
+
+/// Sum of the distances between the two lists paired up in sorted order.
+///
+/// Both columns are sorted with [`radix_sort`], so this is linear in the number of rows. Flipping
+/// the sign bit maps `i32` onto `u32` keeping order and distances, so the keys can be compared
+/// directly.
+fn total_distance<E>(pairs: impl IntoIterator<Item = Result<(i32, i32), E>>) -> Result<u64, E> {
+    let key = |v: i32| (v as u32) ^ (1 << 31);
+    let (mut left, mut right) = (vec![], vec![]);
+    for pair in pairs {
+        let (a, b) = pair?;
+        left.push(key(a));
+        right.push(key(b));
+    }
+    radix_sort(&mut left);
+    radix_sort(&mut right);
+    Ok(left
+        .iter()
+        .zip(&right)
+        .map(|(&a, &b)| u64::from(a.abs_diff(b)))
+        .sum())
+}
+
+/// LSD radix sort, one counting sort pass per byte
+fn radix_sort(values: &mut Vec<u32>) {
+    let mut scratch = vec![0; values.len()];
+    for shift in (0..32).step_by(8) {
+        let digit = |v: u32| (v >> shift) as usize & 0xff;
+        let mut offsets = [0; 256];
+        for &v in values.iter() {
+            offsets[digit(v)] += 1;
+        }
+        let mut start = 0;
+        for offset in offsets.iter_mut() {
+            start += std::mem::replace(offset, start);
+        }
+        for &v in values.iter() {
+            scratch[offsets[digit(v)]] = v;
+            offsets[digit(v)] += 1;
+        }
+        std::mem::swap(values, &mut scratch);
+    }
+}
+
+/// How often each value shows up in either list.
+///
+/// That is all part 2 and the metrics need, and it grows with the number of distinct values
+/// rather than the number of rows.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Counts {
+    left: FxHashMap<i32, u64>,
+    right: FxHashMap<i32, u64>,
+}
+
+impl Counts {
+    fn from_pairs<E>(pairs: impl IntoIterator<Item = Result<(i32, i32), E>>) -> Result<Self, E> {
+        let mut counts = Self::default();
+        for pair in pairs {
+            let (a, b) = pair?;
+            *counts.left.entry(a).or_default() += 1;
+            *counts.right.entry(b).or_default() += 1;
+        }
+        Ok(counts)
+    }
+
+    fn similarity(&self) -> Result<i64> {
+        self.contributions()?
+            .iter()
+            .try_fold(0i64, |acc, c| acc.checked_add(c.score))
+            .ok_or_else(|| eyre!("similarity score does not fit into an i64"))
+    }
+
+    /// Size of the multiset intersection, values shared by both lists counted with multiplicity
+    fn intersection(&self) -> u64 {
+        self.left
+            .iter()
+            .filter_map(|(v, &n)| self.right.get(v).map(|&m| n.min(m)))
+            .sum()
+    }
+
+    /// Multiset Jaccard index: intersection over union, 1 for two empty lists
+    fn jaccard(&self) -> f64 {
+        let intersection = self.intersection();
+        let union = self.left.values().chain(self.right.values()).sum::<u64>() - intersection;
+        match union {
+            0 => 1.0,
+            union => intersection as f64 / union as f64,
+        }
+    }
+
+    /// What every value in both lists adds to the similarity score, by ascending value. Fails
+    /// if a score doesn't fit into an `i64`.
+    fn contributions(&self) -> Result<Vec<Contribution>> {
+        self.left
+            .iter()
+            .filter_map(|(&value, &left)| self.right.get(&value).map(|&right| (value, left, right)))
+            .sorted()
+            .map(|(value, left, right)| {
+                let score = left
+                    .checked_mul(right)
+                    .and_then(|pairs| i64::try_from(pairs).ok())
+                    .and_then(|pairs| pairs.checked_mul(i64::from(value)))
+                    .ok_or_else(|| eyre!("similarity score of {value} does not fit into an i64"))?;
+                Ok(Contribution {
+                    value,
+                    left,
+                    right,
+                    score,
+                })
+            })
+            .collect()
+    }
+
+    /// The `k` values adding the most to the similarity score, biggest first
+    fn top_contributors(&self, k: usize) -> Result<Vec<Contribution>> {
+        Ok(self
+            .contributions()?
+            .into_iter()
+            .sorted_by_key(|c| (std::cmp::Reverse(c.score), c.value))
+            .take(k)
+            .collect())
+    }
+
+    fn report(&self, top: usize) -> Result<String> {
+        let contributions = self.contributions()?;
+        let mut lines = vec![
+            format!("intersection: {}", self.intersection()),
+            format!("jaccard: {:.4}", self.jaccard()),
+            format!("similarity: {}", self.similarity()?),
+            "breakdown:".to_string(),
+        ];
+        lines.extend(contributions.iter().map(|c| format!("  {c}")));
+        lines.push(format!(
+            "top {top}: {}",
+            self.top_contributors(top)?
+                .iter()
+                .map(|c| format!("{} ({})", c.value, c.score))
+                .join(", ")
+        ));
+        Ok(lines.join("\n"))
+    }
+}
+
+/// One value's share of the similarity score
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Contribution {
+    value: i32,
+    left: u64,
+    right: u64,
+    score: i64,
+}
+
+impl std::fmt::Display for Contribution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} left x {} right -> {}",
+            self.value, self.left, self.right, self.score
+        )
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use assert_ok::assert_ok;
+    use proptest::prelude::*;
+    use test_case::test_case;
     const INPUT: &str = "3   4
 4   3
 2   5
@@ -74,16 +332,123 @@ mod tests {
 
     #[test]
     fn test_parse() {
-        let v = Day::parse_input(INPUT);
+        let v = assert_ok!(Day::parse_input(INPUT));
         println!("{v:?}");
     }
 
+    #[test]
+    fn test_parse_error() {
+        let e = Day::parse_input("3   4\n4   x\n").unwrap_err();
+        assert_eq!((e.line, e.column), (2, 5));
+        let e = Day::parse_input("3   4\n4\n").unwrap_err();
+        assert_eq!((e.line, e.column), (2, 2));
+        let e = Day::parse_input("3 4 5").unwrap_err();
+        assert_eq!((e.line, e.column), (1, 5));
+    }
+
+    #[test]
+    fn test_parse_whitespace() {
+        let v = assert_ok!(Day::parse_input(" 3\t4\n\n4 3  \r\n  \n2     5"));
+        assert_eq!(v, (vec![3, 4, 2], vec![4, 3, 5]));
+    }
+
+    #[test_case(Part::Part1, "11")]
+    #[test_case(Part::Part2, "31")]
+    fn test_streaming(part: Part, expected: &str) {
+        let v = assert_ok!(Variant::parse("streaming"));
+        assert_eq!(assert_ok!(Day.run_variant(INPUT, part, &v)), expected);
+    }
+
     #[test]
     fn test_solve_1() {
-        assert_eq!(solve_1(INPUT), 11)
+        assert_eq!(assert_ok!(solve_1(INPUT)), 11)
     }
     #[test]
     fn test_solve_2() {
-        assert_eq!(solve_2(INPUT), 31)
+        assert_eq!(assert_ok!(solve_2(INPUT)), 31)
+    }
+
+    #[test]
+    fn test_metrics() {
+        let v = assert_ok!(Variant::parse("metrics:top=1"));
+        assert_eq!(
+            assert_ok!(Day.run_variant(INPUT, Part::Part2, &v)),
+            "intersection: 4
+jaccard: 0.5000
+similarity: 31
+breakdown:
+  3: 3 left x 3 right -> 27
+  4: 1 left x 1 right -> 4
+top 1: 3 (27)"
+        );
+    }
+
+    #[test]
+    fn test_top_contributors() {
+        let counts = assert_ok!(Counts::from_pairs(Day::pairs("1 1\n1 1\n2 2\n5 5\n-3 -3")));
+        let top = assert_ok!(counts.top_contributors(4))
+            .iter()
+            .map(|c| c.value)
+            .collect_vec();
+        assert_eq!(top, vec![5, 1, 2, -3]);
+        assert_eq!(Counts::default().jaccard(), 1.0);
+    }
+
+    #[test]
+    fn test_similarity_overflow() {
+        let counts = |value, n: u64| Counts {
+            left: FxHashMap::from_iter([(value, n)]),
+            right: FxHashMap::from_iter([(value, n)]),
+        };
+        assert_eq!(assert_ok!(counts(-2, 1 << 31).similarity()), i64::MIN);
+        assert!(counts(2, 1 << 31).similarity().is_err());
+        assert!(counts(1, 1 << 32).similarity().is_err());
+        let big = Counts {
+            left: FxHashMap::from_iter([(3, 1 << 30), (5, 1 << 30)]),
+            right: FxHashMap::from_iter([(3, 1 << 30), (5, 1 << 30)]),
+        };
+        assert!(big.contributions().is_ok());
+        assert!(big.similarity().is_err());
+        assert!(big.report(1).is_err());
+    }
+
+    #[test]
+    fn test_radix_sort() {
+        let mut v = vec![u32::MAX, 0, 256, 1, 65536, 255, 1 << 31, 256];
+        radix_sort(&mut v);
+        assert_eq!(v, vec![0, 1, 255, 256, 256, 65536, 1 << 31, u32::MAX]);
+        let pairs = [(i32::MIN, i32::MAX), (-1, 0)];
+        let d = assert_ok!(total_distance(pairs.map(Ok::<_, ParseError>)));
+        assert_eq!(d, u64::from(u32::MAX) + 1);
+    }
+
+    #[test]
+    fn test_read_pairs() {
+        let r = assert_ok!(Day::solve_streaming(INPUT.as_bytes(), Part::Part1));
+        assert_eq!(r, "11");
+        let e = Day::solve_streaming("1 2\n\n3 x".as_bytes(), Part::Part2).unwrap_err();
+        let e = assert_ok!(e.downcast::<ParseError>());
+        assert_eq!((e.line, e.column), (3, 3));
+
+        let v = assert_ok!(Variant::parse("streaming"));
+        let r = assert_ok!(Day.run_variant_reader(&mut INPUT.as_bytes(), Part::Part2, &v));
+        assert_eq!(r, "31");
+        let v = assert_ok!(Variant::parse("metrics"));
+        let r = assert_ok!(Day.run_variant_reader(&mut INPUT.as_bytes(), Part::Part2, &v));
+        assert!(r.contains("similarity: 31"));
+    }
+
+    proptest! {
+        #[test]
+        fn prop_counts_match_lists(pairs in proptest::collection::vec((-50i32..50, -50i32..50), 0..100)) {
+            let counts = assert_ok!(Counts::from_pairs(pairs.iter().map(|&p| Ok::<_, ParseError>(p))));
+            let (a, b): (Vec<i32>, Vec<i32>) = pairs.iter().copied().unzip();
+            let distance: u64 = a.iter().sorted().zip(b.iter().sorted()).map(|(x, y)| u64::from(x.abs_diff(*y))).sum();
+            let similarity: i64 = a.iter().map(|&x| i64::from(x) * b.iter().filter(|&&y| y == x).count() as i64).sum();
+            prop_assert_eq!(assert_ok!(total_distance(pairs.iter().map(|&p| Ok::<_, ParseError>(p)))), distance);
+            prop_assert_eq!(assert_ok!(counts.similarity()), similarity);
+            prop_assert_eq!(assert_ok!(counts.contributions()).iter().map(|c| c.score).sum::<i64>(), similarity);
+            prop_assert!((0.0..=1.0).contains(&counts.jaccard()));
+        }
     }
 }
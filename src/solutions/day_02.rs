@@ -1,5 +1,7 @@
-use crate::Solver;
-use color_eyre::eyre::{self, Context, Result};
+use std::fmt;
+
+use crate::{parse::ParseError, unknown_variant, Part, Solver, Variant};
+use color_eyre::eyre::{self, eyre, Result};
 use itertools::Itertools;
 
 pub struct Day;
@@ -9,21 +11,53 @@ impl Solver for Day {
         Ok(solve_1(input)?.to_string())
     }
 
-    fn part_2(&self, input: &str) -> Result<String> {
-        Ok(solve_2(input)?.to_string())
+    fn part_2(&self, input: &str) -> Result<Option<String>> {
+        Ok(Some(solve_2(input)?.to_string()))
+    }
+
+    fn variants(&self) -> &'static [&'static str] {
+        &["tolerate", "removals", "diagnose"]
+    }
+
+    /// `tolerate:k=2,min=1,max=3` counts the reports that are safe after removing up to `k`
+    /// levels (by default 0 for part 1 and 1 for part 2), `removals` lists the fewest removals
+    /// each report needs and `diagnose` explains for every report why it is unsafe and which
+    /// single removal fixes it. All of them take the step bounds `min` and `max`.
+    fn run_variant(&self, input: &str, part: Part, variant: &Variant) -> Result<String> {
+        let bounds = StepBounds::new(variant.arg("min", 1)?, variant.arg("max", 3)?)?;
+        let reports = parse_inputs(input)?;
+        match variant.name {
+            "tolerate" => {
+                let k = variant.arg("k", part as usize - 1)?;
+                Ok(count_tolerant(&reports, k, bounds).to_string())
+            }
+            "removals" => Ok(reports
+                .iter()
+                .enumerate()
+                .map(|(i, v)| format!("{}: {}", i + 1, min_removals(v, bounds)))
+                .join("\n")),
+            "diagnose" => Ok(reports
+                .iter()
+                .enumerate()
+                .map(|(i, v)| format!("{}: {}", i + 1, diagnose(v, bounds)))
+                .join("\n")),
+            _ => Err(unknown_variant(variant, self.variants())),
+        }
     }
 }
 
-fn parse_str_of_i32(input: &str) -> Result<Vec<i32>> {
-    let collect = input
-        .split(" ")
+fn parse_str_of_i32(line_no: usize, input: &str) -> Result<Vec<i32>, ParseError> {
+    let mut column = 1;
+    input
+        .split(' ')
         .map(|s: &str| {
-            s.parse::<i32>()
-                .wrap_err_with(|| format!("failed to parse {} to i32", s))
+            let parsed = s
+                .parse::<i32>()
+                .map_err(|_| ParseError::in_line(line_no, input, column, "a number"));
+            column += s.chars().count() + 1;
+            parsed
         })
-        .collect::<Result<Vec<_>>>()?;
-
-    Ok(collect)
+        .collect()
 }
 
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
@@ -35,11 +69,38 @@ enum Safety {
 
 impl From<i32> for Safety {
     fn from(value: i32) -> Self {
-        match value {
-            -3..=-1 => Self::SafeNeg,
-            0 => Self::Unsafe,
-            1..=3 => Self::SafePos,
-            _ => Self::Unsafe,
+        StepBounds::default().classify(value)
+    }
+}
+
+/// How big a step between neighbouring levels may be, in either direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct StepBounds {
+    min: i32,
+    max: i32,
+}
+
+impl Default for StepBounds {
+    fn default() -> Self {
+        Self { min: 1, max: 3 }
+    }
+}
+
+impl StepBounds {
+    fn new(min: i32, max: i32) -> Result<Self> {
+        if min < 1 || min > max {
+            return Err(eyre!("step bounds need 1 <= min <= max, got {min}..={max}"));
+        }
+        Ok(Self { min, max })
+    }
+
+    fn classify(self, step: i32) -> Safety {
+        if (self.min..=self.max).contains(&step) {
+            Safety::SafePos
+        } else if (-self.max..=-self.min).contains(&step) {
+            Safety::SafeNeg
+        } else {
+            Safety::Unsafe
         }
     }
 }
@@ -65,37 +126,175 @@ fn solve_1(input: &str) -> Result<usize> {
 }
 
 fn solve_2(input: &str) -> Result<usize> {
-    let input = parse_inputs(input)?;
+    Ok(parse_inputs(input)?
+        .iter()
+        .filter(|v| safe_with_dampener(v))
+        .count())
+}
+
+/// Whether removing at most one level makes the report safe, in linear time.
+///
+/// The first step that breaks a direction has to lose one of its two levels, otherwise they stay
+/// neighbours, so there are only two removals to try per direction.
+fn safe_with_dampener(levels: &[i32]) -> bool {
+    [Safety::SafePos, Safety::SafeNeg].into_iter().any(|dir| {
+        let first_bad = levels
+            .windows(2)
+            .position(|w| Safety::from(w[0] - w[1]) != dir);
+        match first_bad {
+            None => true,
+            Some(i) => [i, i + 1].into_iter().any(|skip| {
+                levels
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, _)| j != skip)
+                    .map_windows(|&[(_, a), (_, b)]| Safety::from(a - b))
+                    .all(|s| s == dir)
+            }),
+        }
+    })
+}
+
+/// The original dampener, trying every report with one level left out. Quadratic, kept to check
+/// [`safe_with_dampener`] against.
+#[cfg(test)]
+fn safe_with_dampener_reference(v: &[i32]) -> bool {
+    everything_safe(v)
+        || v.iter()
+            .combinations(v.len().saturating_sub(1))
+            .any(everything_safe)
+}
 
-    let result = input
+/// Fewest levels to remove so the rest of the report is safe.
+///
+/// Keeps the longest subsequence in which every step goes the same way within `bounds`, found
+/// with a quadratic longest-chain DP per direction.
+fn min_removals(levels: &[i32], bounds: StepBounds) -> usize {
+    let longest = [Safety::SafePos, Safety::SafeNeg]
         .into_iter()
-        .map(|v| {
-            let is_safe = everything_safe(v.iter());
-            is_safe
-                || v.iter()
-                    .combinations(v.len() - 1)
-                    .map(|comb| {
-                        let x = everything_safe(comb);
-                        x
-                    })
-                    .any(|b| b)
+        .flat_map(|dir| {
+            let mut chain = vec![1; levels.len()];
+            for j in 0..levels.len() {
+                chain[j] = (0..j)
+                    .filter(|&i| bounds.classify(levels[i] - levels[j]) == dir)
+                    .map(|i| chain[i] + 1)
+                    .max()
+                    .unwrap_or(1);
+            }
+            chain
         })
-        .filter(|x| *x)
-        .count();
-    Ok(result)
+        .max()
+        .unwrap_or(0);
+    levels.len() - longest
+}
+
+/// Number of reports that are safe with up to `k` levels removed
+fn count_tolerant(reports: &[Vec<i32>], k: usize, bounds: StepBounds) -> usize {
+    reports
+        .iter()
+        .filter(|v| min_removals(v, bounds) <= k)
+        .count()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Problem {
+    ZeroStep,
+    DirectionFlip,
+    StepTooSmall,
+    StepTooLarge,
+}
+
+/// The first step of a report that breaks the rules, between levels `index` and `index + 1`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Violation {
+    problem: Problem,
+    index: usize,
+    from: i32,
+    to: i32,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problem = match self.problem {
+            Problem::ZeroStep => "zero step",
+            Problem::DirectionFlip => "direction flip",
+            Problem::StepTooSmall => "step too small",
+            Problem::StepTooLarge => "step too large",
+        };
+        write!(
+            f,
+            "{problem} at index {} ({} -> {})",
+            self.index, self.from, self.to
+        )
+    }
+}
+
+/// The direction is set by the first non-zero step
+fn first_violation(levels: &[i32], bounds: StepBounds) -> Option<Violation> {
+    let mut direction = None;
+    levels.windows(2).enumerate().find_map(|(index, w)| {
+        let step = w[1] - w[0];
+        let problem = if step == 0 {
+            Problem::ZeroStep
+        } else if *direction.get_or_insert(step.signum()) != step.signum() {
+            Problem::DirectionFlip
+        } else if step.abs() < bounds.min {
+            Problem::StepTooSmall
+        } else if step.abs() > bounds.max {
+            Problem::StepTooLarge
+        } else {
+            return None;
+        };
+        Some(Violation {
+            problem,
+            index,
+            from: w[0],
+            to: w[1],
+        })
+    })
+}
+
+/// One line explaining what is wrong with a report and which removals would fix it
+fn diagnose(levels: &[i32], bounds: StepBounds) -> String {
+    let Some(violation) = first_violation(levels, bounds) else {
+        return "safe".to_string();
+    };
+    let fixes = (0..levels.len())
+        .filter(|&skip| {
+            let rest = levels
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| i != skip)
+                .map(|(_, &l)| l)
+                .collect_vec();
+            first_violation(&rest, bounds).is_none()
+        })
+        .map(|i| format!("index {i} ({})", levels[i]))
+        .collect_vec();
+    if fixes.is_empty() {
+        format!("unsafe, {violation}, no single removal fixes it")
+    } else {
+        format!(
+            "unsafe, {violation}, fixed by removing {}",
+            fixes.join(" or ")
+        )
+    }
 }
 
 fn parse_inputs(input: &str) -> Result<Vec<Vec<i32>>> {
-    input
+    Ok(input
         .lines()
-        .map(parse_str_of_i32)
-        .collect::<Result<Vec<Vec<_>>>>()
+        .enumerate()
+        .map(|(i, l)| parse_str_of_i32(i + 1, l))
+        .collect::<Result<Vec<Vec<_>>, _>>()?)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use assert_ok::assert_ok;
+    use proptest::prelude::*;
+    use test_case::test_case;
     const INPUT: &str = "7 6 4 2 1
 1 2 7 8 9
 9 7 6 2 1
@@ -115,4 +314,84 @@ mod tests {
         let r = assert_ok!(solve_2(INPUT));
         assert_eq!(SOLUTION_2, r);
     }
+
+    #[test]
+    fn test_parse_error() {
+        let e = parse_str_of_i32(3, "1 22 x3 4").unwrap_err();
+        assert_eq!((e.line, e.column), (3, 6));
+    }
+
+    #[test]
+    fn test_dampener_edges() {
+        assert!(safe_with_dampener(&[5, 1, 2, 3]));
+        assert!(safe_with_dampener(&[1, 2, 3, 9]));
+        assert!(safe_with_dampener(&[1, 5, 2, 3]));
+        assert!(safe_with_dampener(&[3, 1, 2, 3, 4]));
+        assert!(!safe_with_dampener(&[1, 1, 1]));
+        assert!(safe_with_dampener(&[1]));
+    }
+
+    #[test_case(&[7, 6, 4, 2, 1], 0)]
+    #[test_case(&[1, 3, 2, 4, 5], 1)]
+    #[test_case(&[1, 2, 7, 8, 9], 2)]
+    #[test_case(&[9, 7, 6, 2, 1], 2)]
+    #[test_case(&[1, 9, 2, 9, 3, 9, 4], 3)]
+    #[test_case(&[], 0)]
+    fn test_min_removals(levels: &[i32], expected: usize) {
+        assert_eq!(min_removals(levels, StepBounds::default()), expected);
+    }
+
+    #[test]
+    fn test_tolerate_variant() {
+        let tolerate =
+            |v| assert_ok!(Day.run_variant(INPUT, Part::Part1, &assert_ok!(Variant::parse(v))));
+        assert_eq!(tolerate("tolerate"), SOLUTION_1.to_string());
+        assert_eq!(tolerate("tolerate:k=1"), SOLUTION_2.to_string());
+        assert_eq!(tolerate("tolerate:k=2"), "6");
+        assert_eq!(tolerate("tolerate:max=5"), "4");
+        assert_eq!(tolerate("removals").lines().nth(2), Some("3: 2"));
+        assert!(StepBounds::new(0, 3).is_err());
+        assert!(StepBounds::new(4, 3).is_err());
+    }
+
+    #[test]
+    fn test_diagnose() {
+        let v = assert_ok!(Variant::parse("diagnose"));
+        let r = assert_ok!(Day.run_variant(INPUT, Part::Part2, &v));
+        assert_eq!(
+            r,
+            "1: safe
+2: unsafe, step too large at index 1 (2 -> 7), no single removal fixes it
+3: unsafe, step too large at index 2 (6 -> 2), no single removal fixes it
+4: unsafe, direction flip at index 1 (3 -> 2), fixed by removing index 1 (3) or index 2 (2)
+5: unsafe, zero step at index 2 (4 -> 4), fixed by removing index 2 (4) or index 3 (4)
+6: safe"
+        );
+        let bounds = assert_ok!(StepBounds::new(2, 3));
+        assert_eq!(
+            first_violation(&[1, 3, 4], bounds).map(|v| (v.problem, v.index)),
+            Some((Problem::StepTooSmall, 1))
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn prop_dampener_matches_reference(v in proptest::collection::vec(0i32..12, 1..10)) {
+            prop_assert_eq!(safe_with_dampener(&v), safe_with_dampener_reference(&v));
+        }
+
+        #[test]
+        fn prop_min_removals_matches_parts(v in proptest::collection::vec(0i32..12, 1..10)) {
+            let removals = min_removals(&v, StepBounds::default());
+            prop_assert_eq!(removals == 0, everything_safe(&v));
+            prop_assert_eq!(removals <= 1, safe_with_dampener(&v));
+        }
+
+        #[test]
+        fn prop_diagnose_matches_parts(v in proptest::collection::vec(0i32..12, 1..10)) {
+            let d = diagnose(&v, StepBounds::default());
+            prop_assert_eq!(d == "safe", everything_safe(&v));
+            prop_assert_eq!(!d.ends_with("no single removal fixes it"), safe_with_dampener(&v));
+        }
+    }
 }
@@ -1,8 +1,11 @@
-use crate::Solver;
+use std::sync::LazyLock;
+
+use crate::{parse::ParseError, unknown_variant, Part, Solver, Variant};
 use color_eyre::eyre::{eyre, Result};
+use interpreter::{Effect, Interpreter};
 use itertools::Itertools;
 use parser::{parse_all_ops, parse_all_pairs};
-use regex::RegexBuilder;
+use regex::Regex;
 pub struct Day;
 
 impl Solver for Day {
@@ -10,13 +13,44 @@ impl Solver for Day {
         Ok(solve_1_nom(input)?.to_string())
     }
 
-    fn part_2(&self, input: &str) -> Result<String> {
-        Ok(solve_2(input)?.to_string())
+    fn part_2(&self, input: &str) -> Result<Option<String>> {
+        Ok(Some(solve_2(input)?.to_string()))
+    }
+
+    fn variants(&self) -> &'static [&'static str] {
+        &["trace", "regex", "nom", "bytes"]
+    }
+
+    /// `trace` runs the memory through the [`Interpreter`] and prints every instruction it
+    /// executed, with `do()` and `don't()` only taken into account for part 2. `regex`, `nom` and
+    /// `bytes` pick the implementation that finds the instructions.
+    fn run_variant(&self, input: &str, part: Part, variant: &Variant) -> Result<String> {
+        match (variant.name, part) {
+            ("regex", Part::Part1) => Ok(solve_1(input)?.to_string()),
+            ("regex", Part::Part2) => Ok(solve_2_regex(input)?.to_string()),
+            ("nom", Part::Part1) => Ok(solve_1_nom(input)?.to_string()),
+            ("nom", Part::Part2) => Ok(solve_2(input)?.to_string()),
+            ("bytes", Part::Part1) => Ok(solve_1_bytes(input).to_string()),
+            ("bytes", Part::Part2) => Ok(solve_2_bytes(input).to_string()),
+            ("trace", Part::Part1) => Ok(Interpreter::new()
+                .with("mul", 2, 3, Effect::Accumulate(product))
+                .run(input)?
+                .to_string()),
+            ("trace", Part::Part2) => Ok(Interpreter::standard().run(input)?.to_string()),
+            _ => Err(unknown_variant(variant, self.variants())),
+        }
     }
 }
 
+/// Compiled once rather than on every call, so repeated runs and benchmarks only pay for matching
+static MUL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new("mul\\((\\d{1,3}),(\\d{1,3})\\)").expect("valid regex"));
+static MUL_DO_DONT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new("mul\\((\\d{1,3}),(\\d{1,3})\\)|do\\(\\)|don't\\(\\)").expect("valid regex")
+});
+
 fn solve_1(input: &str) -> Result<i64> {
-    let re = RegexBuilder::new("mul\\((\\d{1,3}),(\\d{1,3})\\)").build()?;
+    let re = &*MUL;
 
     let c = re.captures_iter(input).collect_vec();
     // it is ok to unwrap the parse result here, because the regex only returns valid digits in the capture group
@@ -30,8 +64,26 @@ fn solve_1(input: &str) -> Result<i64> {
     Ok(result)
 }
 
+fn solve_2_regex(input: &str) -> Result<i64> {
+    let re = &*MUL_DO_DONT;
+
+    let mut enabled = true;
+    let mut sum = 0;
+    for c in re.captures_iter(input) {
+        match (&c[0], c.get(1).zip(c.get(2))) {
+            ("do()", _) => enabled = true,
+            ("don't()", _) => enabled = false,
+            (_, Some((a, b))) if enabled => {
+                sum += a.as_str().parse::<i64>()? * b.as_str().parse::<i64>()?
+            }
+            _ => {}
+        }
+    }
+    Ok(sum)
+}
+
 fn solve_1_nom(input: &str) -> Result<i64> {
-    let (_, r) = parse_all_pairs(input).map_err(|e| eyre!("failed to parse {e:?}"))?;
+    let (_, r) = parse_all_pairs(input).map_err(|e| ParseError::from_nom(input, e))?;
     Ok(r.iter().map(|(a, b)| a * b).sum())
 }
 
@@ -45,16 +97,15 @@ enum Operation {
 mod parser {
     use nom::{
         self,
-        bytes::complete::{tag, take, take_till},
-        character::complete::digit1,
-        combinator::{map, map_res, not},
-        error::ParseError,
-        multi::{many0, many0_count},
-        sequence::{delimited, preceded, separated_pair, tuple},
-        IResult, InputIter, InputLength, InputTake, Parser,
+        bytes::complete::{tag, take_while_m_n},
+        combinator::map_res,
+        multi::many0,
+        sequence::{delimited, separated_pair},
+        IResult,
     };
 
     use super::Operation;
+    use crate::parse::skip_until;
 
     pub fn parse_mul(input: &str) -> IResult<&str, Operation> {
         let (input, (a, b)) = num_double(input)?;
@@ -64,16 +115,20 @@ mod parser {
     fn num_double(input: &str) -> IResult<&str, (i64, i64)> {
         let (input, (a, b)) = delimited(
             tag("mul("),
-            separated_pair(
-                map_res(digit1, str::parse),
-                tag(","),
-                map_res(digit1, str::parse),
-            ),
+            separated_pair(operand, tag(","), operand),
             tag(")"),
         )(input)?;
         Ok((input, (a, b)))
     }
 
+    /// 1 to 3 digits, like the `\\d{1,3}` of the regex
+    fn operand(input: &str) -> IResult<&str, i64> {
+        map_res(
+            take_while_m_n(1, 3, |c: char| c.is_ascii_digit()),
+            str::parse,
+        )(input)
+    }
+
     pub fn parse_do(input: &str) -> IResult<&str, Operation> {
         let (input, _) = tag("do()")(input)?;
         Ok((input, Operation::Do))
@@ -88,25 +143,13 @@ mod parser {
         Ok((input, op))
     }
 
-    fn until_parser<'a, I, O, E, F>(mut parser: F) -> impl FnMut(I) -> IResult<I, O, E> + 'a
-    where
-        I: Clone + InputLength + InputIter + InputTake + 'a,
-        F: Parser<I, O, E> + 'a,
-        E: ParseError<I> + 'a,
-    {
-        move |input: I| {
-            let (input, _) = many0(preceded(not(|i| parser.parse(i)), take(1u8)))(input)?;
-            parser.parse(input)
-        }
-    }
-
     pub fn parse_all_pairs<'a>(input: &'a str) -> IResult<&'a str, Vec<(i64, i64)>> {
-        let r = many0(until_parser(num_double))(input)?;
+        let r = many0(skip_until(num_double))(input)?;
         Ok(r)
     }
 
     pub fn parse_all_ops(input: &str) -> IResult<&str, Vec<Operation>> {
-        many0(until_parser(parse_operation))(input)
+        many0(skip_until(parse_operation))(input)
     }
 
     #[cfg(test)]
@@ -125,14 +168,321 @@ mod parser {
             assert_eq!(o.1, Operation::Dont);
             let o = assert_ok!(parse_operation("mul(123,456)"));
             assert_eq!(o.1, Operation::Mul(123, 456));
+            assert!(parse_operation("mul(1234,5)").is_err());
+            assert!(parse_operation("mul(1,5678)").is_err());
+        }
+    }
+}
+
+fn solve_1_bytes(input: &str) -> i64 {
+    scanner::Scanner::new(input.as_bytes())
+        .map(|op| match op {
+            Operation::Mul(a, b) => a * b,
+            _ => 0,
+        })
+        .sum()
+}
+
+fn solve_2_bytes(input: &str) -> i64 {
+    let mut enabled = true;
+    let mut sum = 0;
+    for op in scanner::Scanner::new(input.as_bytes()) {
+        match op {
+            Operation::Mul(a, b) if enabled => sum += a * b,
+            Operation::Mul(..) => {}
+            Operation::Do => enabled = true,
+            Operation::Dont => enabled = false,
+        }
+    }
+    sum
+}
+
+/// Finds the operations in a single pass over the bytes, without allocating.
+///
+/// Every instruction starts with `m` or `d`, so `memchr` jumps straight to the candidates. All
+/// instruction bytes are ASCII and so never part of a multi-byte UTF-8 character, which makes
+/// working on bytes safe.
+mod scanner {
+    use memchr::memchr2;
+
+    use super::Operation;
+
+    pub(super) struct Scanner<'a> {
+        bytes: &'a [u8],
+        pos: usize,
+    }
+
+    impl<'a> Scanner<'a> {
+        pub fn new(bytes: &'a [u8]) -> Self {
+            Self { bytes, pos: 0 }
+        }
+
+        /// Parses an operation at the start of `rest`, returning it with its length
+        fn operation(rest: &[u8]) -> Option<(Operation, usize)> {
+            if rest.starts_with(b"do()") {
+                return Some((Operation::Do, 4));
+            }
+            if rest.starts_with(b"don't()") {
+                return Some((Operation::Dont, 7));
+            }
+            let rest = rest.strip_prefix(b"mul(")?;
+            let (a, a_len) = operand(rest)?;
+            let rest = rest[a_len..].strip_prefix(b",")?;
+            let (b, b_len) = operand(rest)?;
+            rest[b_len..].starts_with(b")").then_some(())?;
+            Some((Operation::Mul(a, b), 4 + a_len + 1 + b_len + 1))
+        }
+    }
+
+    /// 1 to 3 leading digits and how many there were
+    fn operand(bytes: &[u8]) -> Option<(i64, usize)> {
+        let len = bytes
+            .iter()
+            .take(3)
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        let value = bytes[..len]
+            .iter()
+            .fold(0, |acc, b| acc * 10 + i64::from(b - b'0'));
+        (len > 0).then_some((value, len))
+    }
+
+    impl Iterator for Scanner<'_> {
+        type Item = Operation;
+
+        fn next(&mut self) -> Option<Operation> {
+            while let Some(i) = memchr2(b'm', b'd', &self.bytes[self.pos..]) {
+                self.pos += i;
+                match Self::operation(&self.bytes[self.pos..]) {
+                    Some((op, len)) => {
+                        self.pos += len;
+                        return Some(op);
+                    }
+                    None => self.pos += 1,
+                }
+            }
+            self.pos = self.bytes.len();
+            None
+        }
+    }
+}
+
+/// `None` if it overflows
+fn product(args: &[i64]) -> Option<i64> {
+    args.iter().try_fold(1i64, |acc, &a| acc.checked_mul(a))
+}
+
+/// A corrupted-memory interpreter that instructions can be registered with.
+///
+/// An instruction is `name(a,b,...)` with exactly as many arguments as registered, each 1 up to
+/// its digit limit long, anything else in the memory is noise.
+mod interpreter {
+    use std::fmt;
+
+    use color_eyre::eyre::{eyre, Result};
+
+    use super::product;
+
+    /// What running an instruction does
+    #[derive(Debug, Clone, Copy)]
+    pub enum Effect {
+        /// Adds the value computed from the arguments to the total, while enabled. `None` means
+        /// the value doesn't fit into an `i64`.
+        Accumulate(fn(&[i64]) -> Option<i64>),
+        Enable,
+        Disable,
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct Opcode {
+        pub name: String,
+        pub arity: usize,
+        /// Longest argument, in digits
+        pub max_digits: usize,
+        pub effect: Effect,
+    }
+
+    /// An instruction found `offset` bytes into the memory
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Instruction<'a> {
+        pub offset: usize,
+        pub text: &'a str,
+        /// Index into the registered opcodes
+        pub opcode: usize,
+        pub args: Vec<i64>,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Action {
+        Added(i64),
+        /// An accumulating instruction while disabled
+        Skipped,
+        Enabled,
+        Disabled,
+    }
+
+    impl fmt::Display for Action {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Action::Added(v) => write!(f, "{v:+}"),
+                Action::Skipped => write!(f, "skipped"),
+                Action::Enabled => write!(f, "enabled"),
+                Action::Disabled => write!(f, "disabled"),
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Step<'a> {
+        pub instruction: Instruction<'a>,
+        pub action: Action,
+        /// Total after this step
+        pub total: i64,
+    }
+
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    pub struct Execution<'a> {
+        pub total: i64,
+        pub trace: Vec<Step<'a>>,
+    }
+
+    impl fmt::Display for Execution<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            for step in &self.trace {
+                writeln!(
+                    f,
+                    "@{} {}: {} (total {})",
+                    step.instruction.offset, step.instruction.text, step.action, step.total
+                )?;
+            }
+            write!(f, "total: {}", self.total)
+        }
+    }
+
+    #[derive(Debug, Clone, Default)]
+    pub struct Interpreter {
+        opcodes: Vec<Opcode>,
+    }
+
+    impl Interpreter {
+        /// An interpreter that knows no instructions yet
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// `mul`, `do` and `don't`, as in the puzzle
+        pub fn standard() -> Self {
+            Self::new()
+                .with("mul", 2, 3, Effect::Accumulate(product))
+                .with("do", 0, 0, Effect::Enable)
+                .with("don't", 0, 0, Effect::Disable)
+        }
+
+        /// Registers an instruction. When several match at the same spot the one registered
+        /// first wins.
+        pub fn with(mut self, name: &str, arity: usize, max_digits: usize, effect: Effect) -> Self {
+            self.opcodes.push(Opcode {
+                name: name.to_string(),
+                arity,
+                max_digits,
+                effect,
+            });
+            self
+        }
+
+        pub fn opcode(&self, instruction: &Instruction) -> &Opcode {
+            &self.opcodes[instruction.opcode]
+        }
+
+        /// All instructions in `memory`, in order
+        pub fn scan<'a>(&self, memory: &'a str) -> Vec<Instruction<'a>> {
+            let mut instructions = vec![];
+            let mut offset = 0;
+            while let Some(c) = memory[offset..].chars().next() {
+                let found = self.opcodes.iter().enumerate().find_map(|(opcode, op)| {
+                    parse_call(&memory[offset..], op).map(|(len, args)| Instruction {
+                        offset,
+                        text: &memory[offset..offset + len],
+                        opcode,
+                        args,
+                    })
+                });
+                match found {
+                    Some(instruction) => {
+                        offset += instruction.text.len();
+                        instructions.push(instruction);
+                    }
+                    None => offset += c.len_utf8(),
+                }
+            }
+            instructions
+        }
+
+        /// Executes every instruction in `memory`, fails if a value or the total overflows
+        pub fn run<'a>(&self, memory: &'a str) -> Result<Execution<'a>> {
+            let mut enabled = true;
+            let mut execution = Execution::default();
+            for instruction in self.scan(memory) {
+                let overflow = || {
+                    eyre!(
+                        "{} at offset {} overflows",
+                        instruction.text,
+                        instruction.offset
+                    )
+                };
+                let action = match self.opcode(&instruction).effect {
+                    Effect::Accumulate(f) if enabled => {
+                        Action::Added(f(&instruction.args).ok_or_else(overflow)?)
+                    }
+                    Effect::Accumulate(_) => Action::Skipped,
+                    Effect::Enable => {
+                        enabled = true;
+                        Action::Enabled
+                    }
+                    Effect::Disable => {
+                        enabled = false;
+                        Action::Disabled
+                    }
+                };
+                if let Action::Added(v) = action {
+                    execution.total = execution.total.checked_add(v).ok_or_else(overflow)?;
+                }
+                execution.trace.push(Step {
+                    instruction,
+                    action,
+                    total: execution.total,
+                });
+            }
+            Ok(execution)
+        }
+    }
+
+    /// Length and arguments of a call to `opcode` at the start of `memory`
+    fn parse_call(memory: &str, opcode: &Opcode) -> Option<(usize, Vec<i64>)> {
+        let mut rest = memory
+            .strip_prefix(opcode.name.as_str())?
+            .strip_prefix('(')?;
+        let mut args = Vec::with_capacity(opcode.arity);
+        for i in 0..opcode.arity {
+            if i > 0 {
+                rest = rest.strip_prefix(',')?;
+            }
+            let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+            if !(1..=opcode.max_digits).contains(&digits) {
+                return None;
+            }
+            args.push(rest[..digits].parse().ok()?);
+            rest = &rest[digits..];
         }
+        rest = rest.strip_prefix(')')?;
+        Some((memory.len() - rest.len(), args))
     }
 }
 
 fn solve_2(input: &str) -> Result<i64> {
     let mut enabled = true;
     let mut sum = 0;
-    let (rest, v) = parse_all_ops(input).map_err(|e| eyre!("failed to parse {e:?}"))?;
+    let (rest, v) = parse_all_ops(input).map_err(|e| ParseError::from_nom(input, e))?;
     for &ops in v.iter() {
         match ops {
             Operation::Mul(a, b) => {
@@ -153,6 +503,8 @@ mod tests {
     use assert_ok::assert_ok;
     use nom::bytes::complete::take_till;
     use parser::parse_all_pairs;
+    use proptest::prelude::*;
+    use test_case::test_case;
     const INPUT_1: &str = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
     const INPUT_2: &str =
         "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
@@ -180,6 +532,109 @@ mod tests {
         assert_eq!(SOLUTION_2, r);
     }
 
+    #[test]
+    fn test_interpreter() {
+        let mul_only = Interpreter::new().with("mul", 2, 3, Effect::Accumulate(product));
+        assert_eq!(assert_ok!(mul_only.run(INPUT_1)).total, SOLUTION_1);
+        assert_eq!(assert_ok!(mul_only.run(INPUT_2)).total, SOLUTION_1);
+        assert_eq!(
+            assert_ok!(Interpreter::standard().run(INPUT_2)).total,
+            SOLUTION_2
+        );
+        assert!(mul_only.scan("mul(1234,5)mul(1,)mul()").is_empty());
+    }
+
+    #[test]
+    fn test_interpreter_extended() {
+        let interpreter = Interpreter::standard()
+            .with("add", 2, 4, Effect::Accumulate(|a| a[0].checked_add(a[1])))
+            .with("neg", 1, 3, Effect::Accumulate(|a| a[0].checked_neg()))
+            .with("pause", 0, 0, Effect::Disable)
+            .with("resume", 0, 0, Effect::Enable);
+        let e = assert_ok!(
+            interpreter.run("add(1000,2)x(neg(7)pause()mul(5,5)resume()*mul(2,2)don't()")
+        );
+        assert_eq!(e.total, 1000 + 2 - 7 + 4);
+        assert_eq!(
+            e.to_string(),
+            "@0 add(1000,2): +1002 (total 1002)
+@13 neg(7): -7 (total 995)
+@19 pause(): disabled (total 995)
+@26 mul(5,5): skipped (total 995)
+@34 resume(): enabled (total 995)
+@43 mul(2,2): +4 (total 999)
+@51 don't(): disabled (total 999)
+total: 999"
+        );
+    }
+
+    #[test]
+    fn test_interpreter_overflow() {
+        let wide = Interpreter::new().with("mul", 3, 9, Effect::Accumulate(product));
+        let r = wide.run("mul(1,2,3)mul(999999999,999999999,999999999)");
+        assert_eq!(
+            r.unwrap_err().to_string(),
+            "mul(999999999,999999999,999999999) at offset 10 overflows"
+        );
+        let big = "mul(999999999,999999999,9)";
+        assert!(wide.run(&big.repeat(2)).is_err());
+    }
+
+    #[test]
+    fn test_trace_variant() {
+        let v = assert_ok!(Variant::parse("trace"));
+        let r = assert_ok!(Day.run_variant(INPUT_2, Part::Part2, &v));
+        assert_eq!(r.lines().last(), Some("total: 48"));
+        let r = assert_ok!(Day.run_variant(INPUT_2, Part::Part1, &v));
+        assert_eq!(r.lines().last(), Some("total: 161"));
+    }
+
+    #[test_case("regex", Part::Part1, INPUT_1, SOLUTION_1)]
+    #[test_case("regex", Part::Part2, INPUT_2, SOLUTION_2)]
+    #[test_case("nom", Part::Part1, INPUT_1, SOLUTION_1)]
+    #[test_case("nom", Part::Part2, INPUT_2, SOLUTION_2)]
+    #[test_case("bytes", Part::Part1, INPUT_1, SOLUTION_1)]
+    #[test_case("bytes", Part::Part2, INPUT_2, SOLUTION_2)]
+    fn test_implementation_variants(name: &str, part: Part, input: &str, expected: i64) {
+        let v = assert_ok!(Variant::parse(name));
+        assert_eq!(
+            assert_ok!(Day.run_variant(input, part, &v)),
+            expected.to_string()
+        );
+    }
+
+    #[test_case("mul(1234,5)mul(12,3)", 36)]
+    #[test_case("mul(123,4567)mul(1,1)", 1)]
+    #[test_case("mul(0012,3)mul(001,3)", 3)]
+    #[test_case("mmul(mul(2,2))", 4)]
+    fn test_operand_digits(input: &str, expected: i64) {
+        assert_eq!(assert_ok!(solve_1(input)), expected);
+        assert_eq!(assert_ok!(solve_1_nom(input)), expected);
+        assert_eq!(solve_1_bytes(input), expected);
+    }
+
+    /// Corrupted memory made of pieces that are likely to form almost-instructions
+    fn memory() -> impl Strategy<Value = String> {
+        let pieces = prop::sample::select(vec![
+            "mul(", "mul", "(", ")", ",", "1", "23", "456", "7890", "do()", "don't()", "do", "n't",
+            "x", " ", "mul(2,3)", "]", "ü",
+        ]);
+        proptest::collection::vec(pieces, 0..40).prop_map(|p| p.concat())
+    }
+
+    proptest! {
+        #[test]
+        fn prop_implementations_agree(memory in memory()) {
+            let regex_1 = solve_1(&memory).unwrap();
+            let regex_2 = solve_2_regex(&memory).unwrap();
+            prop_assert_eq!(solve_1_nom(&memory).unwrap(), regex_1);
+            prop_assert_eq!(solve_2(&memory).unwrap(), regex_2);
+            prop_assert_eq!(Interpreter::standard().run(&memory).unwrap().total, regex_2);
+            prop_assert_eq!(solve_1_bytes(&memory), regex_1);
+            prop_assert_eq!(solve_2_bytes(&memory), regex_2);
+        }
+    }
+
     #[test]
     fn test_parse_all_ops() {
         let o = assert_ok!(parse_all_ops(INPUT_2));
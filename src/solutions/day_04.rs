@@ -1,10 +1,12 @@
-use std::{
-    cmp::Ordering,
-    ops::{Add, Mul, Sub},
-};
+use std::str::FromStr;
 
-use crate::Solver;
+use crate::{
+    geometry::{Direction, Point2},
+    grid::Grid,
+    unknown_variant, Part, Solver, Variant,
+};
 use color_eyre::eyre::{eyre, Result};
+use itertools::Itertools;
 
 pub struct Day;
 
@@ -13,215 +15,303 @@ impl Solver for Day {
         Ok(solve_1(input)?.to_string())
     }
 
-    fn part_2(&self, input: &str) -> Result<String> {
-        Ok(solve_2(input)?.to_string())
+    fn part_2(&self, input: &str) -> Result<Option<String>> {
+        Ok(Some(solve_2(input)?.to_string()))
+    }
+
+    fn variants(&self) -> &'static [&'static str] {
+        &["search", "template"]
+    }
+
+    /// `search:words=XMAS/MAS,directions=straight,wrap=true,fold_case=true` looks for any
+    /// `/`-separated words and lists every match. `directions` is `straight`, `diagonal` or
+    /// `all` (the default). `template:pattern=M.S/.A./M.S` finds a pattern with `/` between its
+    /// rows and `.` as wildcard, in every rotation and reflection.
+    fn run_variant(&self, input: &str, part: Part, variant: &Variant) -> Result<String> {
+        match variant.name {
+            "search" => {
+                let grid = Grid::chars(input)?;
+                let words: String = variant.arg("words", XMAS.to_string())?;
+                let options = SearchOptions {
+                    directions: variant.arg("directions", Directions::All)?,
+                    wrap: variant.arg("wrap", false)?,
+                    fold_case: variant.arg("fold_case", false)?,
+                };
+                let words = words.split('/').collect_vec();
+                Ok(search(&grid, &words, options)
+                    .iter()
+                    .map(WordMatches::to_string)
+                    .join("\n"))
+            }
+            "template" => {
+                let grid = Grid::chars(input)?;
+                let pattern: String = variant.arg("pattern", X_MAS.replace('\n', "/"))?;
+                let matches = Template::parse(&pattern.replace('/', "\n"))?.find(&grid);
+                let mut lines = vec![format!("{} matches", matches.len())];
+                lines.extend(
+                    matches.iter().map(|m| {
+                        format!("  ({}, {}) {}", m.top_left.x, m.top_left.y, m.orientation)
+                    }),
+                );
+                Ok(lines.join("\n"))
+            }
+            _ => Err(unknown_variant(variant, self.variants())),
+        }
     }
 }
 
 fn solve_1(input: &str) -> Result<usize> {
-    let b = Board::new(input);
+    let b = Grid::chars(input)?;
 
-    Ok(b.find_all_char('X')
+    Ok(find_all_char(&b, 'X')
         .into_iter()
-        .map(|start| Direction::iterator().map(move |d| (start, d)))
-        .flatten()
-        .map(|(start, dir)| b.check_xmas(start, dir))
+        .flat_map(|start| Direction::iterator().map(move |d| (start, d)))
+        .map(|(start, dir)| check_xmas(&b, start, dir))
         .filter(|c| *c)
         .count())
 }
 fn solve_2(input: &str) -> Result<usize> {
-    let b = Board::new(input);
-
-    Ok(b.find_all_char('A')
-        .into_iter()
-        .map(|a| b.check_cross_mas(a))
-        .filter(|c| *c)
-        .count())
-}
-
-#[derive(Debug)]
-struct Board {
-    board: Vec<Vec<char>>,
-    width: usize,
-    height: usize,
-}
+    let b = Grid::chars(input)?;
 
-#[derive(Debug, PartialEq, Clone, Copy)]
-enum Direction {
-    Front,
-    UpFront,
-    Up,
-    UpBack,
-    Back,
-    DownBack,
-    Down,
-    DownFront,
+    Ok(Template::parse(X_MAS)?.find(&b).len())
 }
 
-impl From<Direction> for Point2 {
-    fn from(value: Direction) -> Self {
-        let (x, y) = match value {
-            Direction::Front => (1, 0),
-            Direction::UpFront => (1, -1),
-            Direction::Up => (0, -1),
-            Direction::UpBack => (-1, -1),
-            Direction::Back => (-1, 0),
-            Direction::DownBack => (-1, 1),
-            Direction::Down => (0, 1),
-            Direction::DownFront => (1, 1),
-        };
-        Point2 { x, y }
-    }
+const XMAS: &str = "XMAS";
+/// Two `MAS` crossing on their `A`, matched in all orientations
+const X_MAS: &str = "M.S
+.A.
+M.S";
+
+fn find_all_char(board: &Grid<char>, target: char) -> Vec<Point2> {
+    board
+        .positions(|&c| c == target)
+        .filter_map(Point2::from_grid)
+        .collect()
 }
 
-impl Direction {
-    fn iterator() -> impl Iterator<Item = Direction> {
-        use Direction::*;
-        [Front, UpFront, Up, UpBack, Back, DownBack, Down, DownFront]
-            .iter()
-            .cloned()
-    }
+fn check_xmas(board: &Grid<char>, start: Point2, dir: Direction) -> bool {
+    let incr: Point2 = dir.into();
 
-    fn opposite(&self) -> Direction {
-        use Direction::*;
-        match self {
-            Front => Back,
-            UpFront => DownBack,
-            Up => Down,
-            UpBack => DownFront,
-            Back => Front,
-            DownBack => UpFront,
-            Down => Up,
-            DownFront => UpBack,
+    for (i, xchar) in XMAS.char_indices() {
+        let next = start + (incr * i as i32);
+        if let Some(c) = board.get_point(next) {
+            if *c != xchar {
+                return false;
+            }
+        } else {
+            return false;
         }
     }
+    true
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-struct Point2 {
-    x: i32,
-    y: i32,
+/// How a template was turned: mirrored left to right first if `mirrored`, then rotated
+/// counter-clockwise
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Orientation {
+    mirrored: bool,
+    quarter_turns: u8,
 }
 
-impl Add for Point2 {
-    type Output = Point2;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        Self {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
+impl std::fmt::Display for Orientation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}°", u32::from(self.quarter_turns) * 90)?;
+        if self.mirrored {
+            write!(f, " mirrored")?;
         }
+        Ok(())
     }
 }
 
-impl Sub for Point2 {
-    type Output = Point2;
-
-    fn sub(self, rhs: Self) -> Self::Output {
-        Self {
-            x: self.x - rhs.x,
-            y: self.y - rhs.y,
-        }
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TemplateMatch {
+    top_left: Point2,
+    orientation: Orientation,
 }
 
-impl Mul<i32> for Point2 {
-    type Output = Point2;
-
-    fn mul(self, rhs: i32) -> Self::Output {
-        Self {
-            x: self.x * rhs,
-            y: self.y * rhs,
-        }
-    }
+/// A rectangular pattern of letters where `.` matches anything
+#[derive(Debug, Clone)]
+struct Template {
+    /// Every distinct way the pattern can be rotated and mirrored
+    orientations: Vec<(Orientation, Grid<Option<char>>)>,
 }
 
-const XMAS: &str = "XMAS";
-const MAS: &str = "MAS";
-
-impl Board {
-    fn new(board: &str) -> Self {
-        let width = board.lines().next().unwrap().len();
-        let height = board.lines().count();
-        let board = board.lines().map(|l| l.chars().collect()).collect();
-        Self {
-            board,
-            width,
-            height,
+impl Template {
+    fn parse(pattern: &str) -> Result<Self> {
+        let base = Grid::parse(pattern, |c| Some((c != '.').then_some(c)))?;
+        if base.width() == 0 {
+            return Err(eyre!("template is empty"));
         }
-    }
-
-    fn valid_coord(&self, p: Point2) -> bool {
-        p.x < self.width as i32 && p.y < self.height as i32
-    }
 
-    fn get(&self, p: &Point2) -> Option<&char> {
-        if let Some(l) = self.board.get(p.y as usize) {
-            if let Some(c) = l.get(p.x as usize) {
-                return Some(c);
+        let mut orientations: Vec<(Orientation, Grid<Option<char>>)> = vec![];
+        for mirrored in [false, true] {
+            let mut cells = if mirrored {
+                base.flip_horizontal()
+            } else {
+                base.clone()
+            };
+            for quarter_turns in 0..4 {
+                if orientations.iter().all(|(_, seen)| *seen != cells) {
+                    let orientation = Orientation {
+                        mirrored,
+                        quarter_turns,
+                    };
+                    orientations.push((orientation, cells.clone()));
+                }
+                cells = cells.rotate_left();
             }
         }
-        None
+        Ok(Self { orientations })
     }
 
-    fn find_all_char(&self, target: char) -> Vec<Point2> {
-        self.board
+    /// Every spot and orientation on `board` where the template fits. Orientations that look the
+    /// same are only tried once, but different orientations fitting the same spot all count,
+    /// like `a.\n.a` and its rotation `.a\na.` on a board full of `a`.
+    fn find(&self, board: &Grid<char>) -> Vec<TemplateMatch> {
+        self.orientations
             .iter()
-            .enumerate()
-            .map(|(i, l)| {
-                l.iter().enumerate().filter_map(move |(j, c)| {
-                    if target == *c {
-                        Some(Point2 {
-                            y: i as i32,
-                            x: j as i32,
+            .flat_map(|(orientation, pattern)| {
+                let columns = (board.width() + 1).saturating_sub(pattern.width());
+                let rows = (board.height() + 1).saturating_sub(pattern.height());
+                (0..rows)
+                    .flat_map(move |y| (0..columns).map(move |x| (x, y)))
+                    .filter(move |&(x, y)| {
+                        pattern.cells().all(|((dx, dy), cell)| {
+                            cell.is_none_or(|c| board.get(x + dx, y + dy) == Some(&c))
                         })
-                    } else {
-                        None
-                    }
-                })
+                    })
+                    .map(move |(x, y)| TemplateMatch {
+                        top_left: Point2::new(x as i32, y as i32),
+                        orientation: *orientation,
+                    })
             })
-            .flatten()
             .collect()
     }
+}
 
-    fn check_xmas(&self, start: Point2, dir: Direction) -> bool {
-        let incr: Point2 = dir.into();
+/// Which ways a word may run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Directions {
+    /// Along the rows and columns
+    Straight,
+    Diagonal,
+    All,
+}
 
-        for (i, xchar) in XMAS.char_indices() {
-            let next = start + (incr * i as i32);
-            if let Some(c) = self.get(&next) {
-                if *c != xchar {
-                    return false;
-                }
-            } else {
-                return false;
+impl Directions {
+    fn iter(self) -> impl Iterator<Item = Direction> {
+        Direction::iterator().filter(move |&d| {
+            let step: Point2 = d.into();
+            let diagonal = step.x != 0 && step.y != 0;
+            match self {
+                Directions::Straight => !diagonal,
+                Directions::Diagonal => diagonal,
+                Directions::All => true,
             }
+        })
+    }
+}
+
+impl FromStr for Directions {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "straight" => Ok(Directions::Straight),
+            "diagonal" => Ok(Directions::Diagonal),
+            "all" => Ok(Directions::All),
+            _ => Err("expected straight, diagonal or all".to_string()),
         }
-        true
     }
+}
 
-    fn check_diag_mas(&self, start: Point2, dir: Direction) -> bool {
-        let d_last: Point2 = dir.opposite().into();
-        let dir: Point2 = dir.into();
-        if let (Some(first), Some(last)) = (self.get(&(dir + start)), self.get(&(d_last + start))) {
-            *first == 'M' && *last == 'S'
-        } else {
-            false
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SearchOptions {
+    directions: Directions,
+    /// Words running off one edge continue at the opposite one
+    wrap: bool,
+    /// Compare letters ignoring case
+    fold_case: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            directions: Directions::All,
+            wrap: false,
+            fold_case: false,
         }
     }
+}
 
-    fn check_cross_mas(&self, start: Point2) -> bool {
-        use Direction::{DownBack, DownFront, UpBack, UpFront};
-        const CROSS: [Direction; 4] = [UpBack, UpFront, DownBack, DownFront];
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct WordMatch {
+    start: Point2,
+    direction: Direction,
+}
 
-        CROSS
-            .iter()
-            .map(|&dir| self.check_diag_mas(start, dir))
-            .filter(|c| *c)
-            .count() == 2
+/// Every place a word was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WordMatches {
+    word: String,
+    matches: Vec<WordMatch>,
+}
+
+impl std::fmt::Display for WordMatches {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.word, self.matches.len())?;
+        for m in &self.matches {
+            write!(f, "\n  ({}, {}) {:?}", m.start.x, m.start.y, m.direction)?;
+        }
+        Ok(())
     }
 }
 
+/// Looks for each of `words` starting at every cell in every allowed direction. Palindromes are
+/// found twice, once each way.
+fn search(board: &Grid<char>, words: &[&str], options: SearchOptions) -> Vec<WordMatches> {
+    let starts = (0..board.height())
+        .flat_map(|y| (0..board.width()).map(move |x| Point2::new(x as i32, y as i32)))
+        .collect_vec();
+    words
+        .iter()
+        .map(|&word| {
+            let letters = word.chars().collect_vec();
+            let matches = starts
+                .iter()
+                .filter(|_| !letters.is_empty())
+                .flat_map(|&start| options.directions.iter().map(move |d| (start, d)))
+                .filter(|&(start, direction)| word_at(board, &letters, start, direction, options))
+                .map(|(start, direction)| WordMatch { start, direction })
+                .collect();
+            WordMatches {
+                word: word.to_string(),
+                matches,
+            }
+        })
+        .collect()
+}
+
+fn word_at(
+    board: &Grid<char>,
+    letters: &[char],
+    start: Point2,
+    direction: Direction,
+    options: SearchOptions,
+) -> bool {
+    let step: Point2 = direction.into();
+    let (width, height) = (board.width() as i32, board.height() as i32);
+    letters.iter().enumerate().all(|(i, &letter)| {
+        let mut p = start + step * i as i32;
+        if options.wrap {
+            p = Point2::new(p.x.rem_euclid(width), p.y.rem_euclid(height));
+        }
+        board.get_point(p).is_some_and(|&c| {
+            c == letter || (options.fold_case && c.to_lowercase().eq(letter.to_lowercase()))
+        })
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -260,8 +350,8 @@ M.M.M.M.M.
 
     #[test]
     fn test_build_1() {
-        let b = Board::new(INPUT_1A);
-        let v = b.find_all_char('X');
+        let b = assert_ok!(Grid::chars(INPUT_1A));
+        let v = find_all_char(&b, 'X');
         assert_eq!(
             v,
             vec![
@@ -273,12 +363,12 @@ M.M.M.M.M.
         );
         let f: Vec<_> = v
             .iter()
-            .map(|i| b.check_xmas(*i, Direction::Front))
+            .map(|i| check_xmas(&b, *i, Direction::Front))
             .collect();
         assert_eq!(f, vec![false, false, true, false]);
         let f: Vec<_> = v
             .iter()
-            .map(|i| b.check_xmas(*i, Direction::Back))
+            .map(|i| check_xmas(&b, *i, Direction::Back))
             .collect();
         assert_eq!(f, vec![false, true, false, false]);
     }
@@ -291,6 +381,116 @@ M.M.M.M.M.
         assert_eq!(SOLUTION_1A, r);
     }
 
+    #[test]
+    fn test_search() {
+        let b = assert_ok!(Grid::chars(INPUT_1B));
+        let found = search(&b, &[XMAS, "SAMX", "MAS"], SearchOptions::default());
+        let counts = found.iter().map(|w| w.matches.len()).collect_vec();
+        assert_eq!(counts[0], SOLUTION_1B);
+        assert_eq!(counts[0], counts[1]);
+
+        let straight = SearchOptions {
+            directions: Directions::Straight,
+            ..Default::default()
+        };
+        let diagonal = SearchOptions {
+            directions: Directions::Diagonal,
+            ..Default::default()
+        };
+        let straight = search(&b, &[XMAS], straight)[0].matches.len();
+        let diagonal = search(&b, &[XMAS], diagonal)[0].matches.len();
+        assert_eq!(straight + diagonal, SOLUTION_1B);
+    }
+
+    #[test]
+    fn test_search_options() {
+        let b = assert_ok!(Grid::chars("sXmA\nabcd"));
+        let wrap = SearchOptions {
+            wrap: true,
+            fold_case: true,
+            ..Default::default()
+        };
+        let found = search(&b, &["xmas", "AX", ""], wrap);
+        assert_eq!(
+            found[0].matches,
+            vec![WordMatch {
+                start: Point2::new(1, 0),
+                direction: Direction::Front
+            }]
+        );
+        assert_eq!(found[1].matches.len(), 2);
+        assert!(found[2].matches.is_empty());
+        assert!(search(&b, &["xmas"], SearchOptions::default())[0]
+            .matches
+            .is_empty());
+    }
+
+    #[test]
+    fn test_search_variant() {
+        let v = assert_ok!(Variant::parse("search:words=XMAS/MAS,directions=straight"));
+        let r = assert_ok!(Day.run_variant(INPUT_1A, Part::Part1, &v));
+        assert_eq!(
+            r,
+            "XMAS: 3
+  (4, 1) Back
+  (0, 3) Front
+  (1, 4) Up
+MAS: 3
+  (3, 1) Back
+  (1, 3) Front
+  (1, 3) Up"
+        );
+        let v = assert_ok!(Variant::parse("search:directions=sideways"));
+        assert!(Day.run_variant(INPUT_1A, Part::Part1, &v).is_err());
+    }
+
+    #[test]
+    fn test_template_orientations() {
+        let x_mas = assert_ok!(Template::parse(X_MAS));
+        assert_eq!(x_mas.orientations.len(), 4);
+        let l = assert_ok!(Template::parse("AB\nC."));
+        assert_eq!(l.orientations.len(), 8);
+        let square = assert_ok!(Template::parse("..\n.."));
+        assert_eq!(square.orientations.len(), 1);
+        assert!(Template::parse("").is_err());
+        assert!(Template::parse("ab\nc").is_err());
+    }
+
+    #[test]
+    fn test_template_find() {
+        let b = assert_ok!(Grid::chars("ab.\ncd.\n.ca"));
+        let found = assert_ok!(Template::parse("ab\ncd")).find(&b);
+        assert_eq!(
+            found,
+            vec![TemplateMatch {
+                top_left: Point2::new(0, 0),
+                orientation: Orientation {
+                    mirrored: false,
+                    quarter_turns: 0
+                }
+            }]
+        );
+        let found = assert_ok!(Template::parse("a.\n.d")).find(&b);
+        let spots = found.iter().map(|m| m.top_left).collect_vec();
+        assert_eq!(spots, vec![Point2::new(0, 0), Point2::new(1, 1)]);
+
+        let b = assert_ok!(Grid::chars("aa\naa"));
+        let found = assert_ok!(Template::parse("a.\n.a")).find(&b);
+        let spots = found.iter().map(|m| m.top_left).collect_vec();
+        assert_eq!(spots, vec![Point2::new(0, 0), Point2::new(0, 0)]);
+    }
+
+    #[test]
+    fn test_template_variant() {
+        let v = assert_ok!(Variant::parse("template"));
+        let r = assert_ok!(Day.run_variant(INPUT_2, Part::Part2, &v));
+        assert_eq!(r.lines().next(), Some("9 matches"));
+        // rotations only cover the straight directions, the other 10 XMAS run diagonally
+        let v = assert_ok!(Variant::parse("template:pattern=XMAS"));
+        let r = assert_ok!(Day.run_variant(INPUT_1B, Part::Part1, &v));
+        assert_eq!(r.lines().next(), Some("8 matches"));
+    }
+
     #[test]
     fn test_2() {
         let r = assert_ok!(solve_2(INPUT_2));
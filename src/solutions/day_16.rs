@@ -8,8 +8,8 @@ impl Solver for Day {
         Ok(solve_1(input)?.to_string())
     }
 
-    fn part_2(&self, input: &str) -> Result<String> {
-        Ok(solve_2(input)?.to_string())
+    fn part_2(&self, input: &str) -> Result<Option<String>> {
+        Ok(Some(solve_2(input)?.to_string()))
     }
 }
 
@@ -1,5 +1,6 @@
 use crate::Solver;
 use color_eyre::eyre::{eyre, Result};
+use rayon::prelude::*;
 
 pub struct Day;
 
@@ -8,34 +9,177 @@ impl Solver for Day {
         Ok(solve_1(input)?.to_string())
     }
 
-    fn part_2(&self, input: &str) -> Result<String> {
-        Ok(solve_2(input)?.to_string())
+    fn part_2(&self, input: &str) -> Result<Option<String>> {
+        Ok(Some(solve_2(input)?.to_string()))
     }
 }
 
+const THRESHOLD: usize = 100;
+
 fn solve_1(input: &str) -> Result<usize> {
-    Err(eyre!("not yet implemented"))
+    count_cheats(input, 2, THRESHOLD)
 }
 fn solve_2(input: &str) -> Result<usize> {
-    Err(eyre!("not yet implemented"))
+    count_cheats(input, 20, THRESHOLD)
+}
+
+/// Counts all cheats of at most `cheat_len` picoseconds that save at least `threshold` picoseconds
+fn count_cheats(input: &str, cheat_len: usize, threshold: usize) -> Result<usize> {
+    let track = Track::new(input)?.path()?;
+
+    // walking the track once gives us the distance from the start for free: it's the index.
+    // a cheat from `i` to `j` saves `(j - i) - manhattan(i, j)`, so `j` has to be at least
+    // `threshold + 2` steps further down the track to be worth looking at
+    let result = (0..track.len())
+        .into_par_iter()
+        .map(|i| {
+            let (ax, ay) = track[i];
+            track
+                .iter()
+                .enumerate()
+                .skip(i + threshold + 2)
+                .filter(|(j, &(bx, by))| {
+                    let dist = ax.abs_diff(bx) + ay.abs_diff(by);
+                    dist <= cheat_len && j - i - dist >= threshold
+                })
+                .count()
+        })
+        .sum();
+
+    Ok(result)
+}
+
+#[derive(Debug)]
+struct Track {
+    walls: Vec<bool>,
+    width: usize,
+    start: (usize, usize),
+    end: (usize, usize),
+}
+
+impl Track {
+    fn new(input: &str) -> Result<Self> {
+        let width = input
+            .lines()
+            .next()
+            .ok_or_else(|| eyre!("empty racetrack"))?
+            .len();
+        let mut walls = Vec::with_capacity(width * width);
+        let mut start = None;
+        let mut end = None;
+
+        for (y, line) in input.lines().enumerate() {
+            if line.len() != width {
+                return Err(eyre!(
+                    "line {y} has length {}, expected {width}",
+                    line.len()
+                ));
+            }
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    '#' => walls.push(true),
+                    '.' => walls.push(false),
+                    'S' => {
+                        start = Some((x, y));
+                        walls.push(false);
+                    }
+                    'E' => {
+                        end = Some((x, y));
+                        walls.push(false);
+                    }
+                    _ => return Err(eyre!("unexpected character {c:?} at {x},{y}")),
+                }
+            }
+        }
+
+        Ok(Self {
+            walls,
+            width,
+            start: start.ok_or_else(|| eyre!("racetrack has no start"))?,
+            end: end.ok_or_else(|| eyre!("racetrack has no end"))?,
+        })
+    }
+
+    fn is_wall(&self, (x, y): (usize, usize)) -> bool {
+        self.walls.get(y * self.width + x).copied().unwrap_or(true)
+    }
+
+    /// Follows the single track from start to end
+    fn path(&self) -> Result<Vec<(usize, usize)>> {
+        let mut path = vec![self.start];
+        let mut prev = None;
+        let mut current = self.start;
+
+        while current != self.end {
+            let (x, y) = current;
+            let next = [
+                (x + 1, y),
+                (x.wrapping_sub(1), y),
+                (x, y + 1),
+                (x, y.wrapping_sub(1)),
+            ]
+            .into_iter()
+            .filter(|&p| p.0 < self.width && Some(p) != prev && !self.is_wall(p))
+            .collect::<Vec<_>>();
+
+            match next[..] {
+                [n] => {
+                    prev = Some(current);
+                    current = n;
+                    path.push(n);
+                }
+                [] => return Err(eyre!("dead end at {x},{y}")),
+                _ => return Err(eyre!("track forks at {x},{y}")),
+            }
+        }
+
+        Ok(path)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use assert_ok::assert_ok;
-    const INPUT: &str = "";
-    const SOLUTION_1: usize = 0;
-    const SOLUTION_2: usize = 0;
+    use test_case::test_case;
+    const INPUT: &str = "###############
+#...#...#.....#
+#.#.#.#.#.###.#
+#S#...#.#.#...#
+#######.#.#.###
+#######.#.#...#
+#######.#.###.#
+###..E#...#...#
+###.#######.###
+#...###...#...#
+#.#####.#.###.#
+#.#...#.#.#...#
+#.#.#.#.#.#.###
+#...#...#...###
+###############";
 
     #[test]
-    fn test_1() {
-        let r = assert_ok!(solve_1(INPUT));
-        assert_eq!(SOLUTION_1, r);
+    fn test_path() {
+        let t = assert_ok!(Track::new(INPUT));
+        let p = assert_ok!(t.path());
+        // the fastest (and only) route takes 84 picoseconds
+        assert_eq!(p.len(), 85);
     }
-    #[test]
-    fn test_2() {
-        let r = assert_ok!(solve_2(INPUT));
-        assert_eq!(SOLUTION_2, r);
+
+    #[test_case(64, 1)]
+    #[test_case(40, 2)]
+    #[test_case(20, 5)]
+    #[test_case(2, 44)]
+    fn test_1(threshold: usize, expected: usize) {
+        let r = assert_ok!(count_cheats(INPUT, 2, threshold));
+        assert_eq!(expected, r);
+    }
+
+    #[test_case(76, 3)]
+    #[test_case(74, 7)]
+    #[test_case(50, 285)]
+    fn test_2(threshold: usize, expected: usize) {
+        let r = assert_ok!(count_cheats(INPUT, 20, threshold));
+        assert_eq!(expected, r);
     }
 }
@@ -1,5 +1,5 @@
 use crate::Solver;
-use color_eyre::eyre::{eyre, Result};
+use color_eyre::eyre::{eyre, Context, Result};
 
 pub struct Day;
 
@@ -8,25 +8,217 @@ impl Solver for Day {
         Ok(solve_1(input)?.to_string())
     }
 
-    fn part_2(&self, input: &str) -> Result<String> {
-        Ok(solve_2(input)?.to_string())
+    fn part_2(&self, input: &str) -> Result<Option<String>> {
+        Ok(Some(solve_2(input)?.to_string()))
     }
 }
 
-fn solve_1(input: &str) -> Result<usize> {
-    Err(eyre!("not yet implemented"))
+fn solve_1(input: &str) -> Result<u64> {
+    solve(input, 2)
 }
-fn solve_2(input: &str) -> Result<usize> {
-    Err(eyre!("not yet implemented"))
+fn solve_2(input: &str) -> Result<u64> {
+    solve(input, 25)
+}
+
+/// Sums up the complexities of all codes, with `robots` directional keypads between us and the door
+fn solve(input: &str, robots: usize) -> Result<u64> {
+    let costs = CostTable::new(robots);
+
+    input
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(|code| {
+            let presses = costs.code(code)?;
+            let numeric: u64 = code
+                .trim_end_matches('A')
+                .parse()
+                .wrap_err_with(|| format!("code {code} has no numeric part"))?;
+            Ok(presses * numeric)
+        })
+        .sum()
+}
+
+/// A keypad layout, the gap is the one position a robot arm must never point at
+struct Keypad {
+    keys: &'static [(char, (i32, i32))],
+    gap: (i32, i32),
+}
+
+const NUMERIC: Keypad = Keypad {
+    keys: &[
+        ('7', (0, 0)),
+        ('8', (1, 0)),
+        ('9', (2, 0)),
+        ('4', (0, 1)),
+        ('5', (1, 1)),
+        ('6', (2, 1)),
+        ('1', (0, 2)),
+        ('2', (1, 2)),
+        ('3', (2, 2)),
+        ('0', (1, 3)),
+        ('A', (2, 3)),
+    ],
+    gap: (0, 3),
+};
+
+const DIRECTIONAL: Keypad = Keypad {
+    keys: &[
+        ('^', (1, 0)),
+        ('A', (2, 0)),
+        ('<', (0, 1)),
+        ('v', (1, 1)),
+        ('>', (2, 1)),
+    ],
+    gap: (0, 0),
+};
+
+const DIR_KEYS: usize = 5;
+
+impl Keypad {
+    fn position(&self, key: char) -> Result<(i32, i32)> {
+        self.keys
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, p)| *p)
+            .ok_or_else(|| eyre!("key {key:?} is not on this keypad"))
+    }
+
+    /// All sensible ways to get from `from` to `to` and press it, as directional key presses.
+    ///
+    /// Zig-zagging is never cheaper than going straight, so only the horizontal-first and the
+    /// vertical-first route are candidates, minus the one that would sweep over the gap.
+    fn routes(&self, from: char, to: char) -> Result<Vec<Vec<char>>> {
+        let (fx, fy) = self.position(from)?;
+        let (tx, ty) = self.position(to)?;
+        let (dx, dy) = (tx - fx, ty - fy);
+
+        let horizontal =
+            std::iter::repeat_n(if dx < 0 { '<' } else { '>' }, dx.unsigned_abs() as usize);
+        let vertical =
+            std::iter::repeat_n(if dy < 0 { '^' } else { 'v' }, dy.unsigned_abs() as usize);
+
+        let mut routes = Vec::with_capacity(2);
+        if (tx, fy) != self.gap {
+            routes.push(
+                horizontal
+                    .clone()
+                    .chain(vertical.clone())
+                    .chain(['A'])
+                    .collect(),
+            );
+        }
+        if (fx, ty) != self.gap {
+            let route: Vec<_> = vertical.chain(horizontal).chain(['A']).collect();
+            if !routes.contains(&route) {
+                routes.push(route);
+            }
+        }
+        Ok(routes)
+    }
+}
+
+fn dir_index(key: char) -> usize {
+    match key {
+        '^' => 0,
+        'A' => 1,
+        '<' => 2,
+        'v' => 3,
+        '>' => 4,
+        _ => unreachable!("not a directional key: {key:?}"),
+    }
+}
+
+/// How many of our own button presses it takes to make the outermost robot move from one
+/// directional key to another and press it
+struct CostTable([[u64; DIR_KEYS]; DIR_KEYS]);
+
+impl CostTable {
+    fn new(robots: usize) -> Self {
+        // we press the buttons ourselves, every press costs exactly one
+        let mut table = [[1; DIR_KEYS]; DIR_KEYS];
+
+        for _ in 0..robots {
+            let mut next = [[0; DIR_KEYS]; DIR_KEYS];
+            for &(from, _) in DIRECTIONAL.keys {
+                for &(to, _) in DIRECTIONAL.keys {
+                    next[dir_index(from)][dir_index(to)] = DIRECTIONAL
+                        .routes(from, to)
+                        .expect("directional keys are on the directional keypad")
+                        .iter()
+                        .map(|r| Self::sequence(&table, r))
+                        .min()
+                        .expect("there is always a route");
+                }
+            }
+            table = next;
+        }
+
+        Self(table)
+    }
+
+    /// Cost of typing `keys` with the arm starting on `A`
+    fn sequence(table: &[[u64; DIR_KEYS]; DIR_KEYS], keys: &[char]) -> u64 {
+        keys.iter()
+            .fold(('A', 0), |(prev, sum), &k| {
+                (k, sum + table[dir_index(prev)][dir_index(k)])
+            })
+            .1
+    }
+
+    /// Cost of typing a code on the numeric keypad
+    fn code(&self, code: &str) -> Result<u64> {
+        let mut prev = 'A';
+        let mut sum = 0;
+        for key in code.chars() {
+            sum += NUMERIC
+                .routes(prev, key)?
+                .iter()
+                .map(|r| Self::sequence(&self.0, r))
+                .min()
+                .ok_or_else(|| eyre!("no route from {prev} to {key}"))?;
+            prev = key;
+        }
+        Ok(sum)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use assert_ok::assert_ok;
-    const INPUT: &str = "";
-    const SOLUTION_1: usize = 0;
-    const SOLUTION_2: usize = 0;
+    use test_case::test_case;
+    const INPUT: &str = "029A
+980A
+179A
+456A
+379A";
+    const SOLUTION_1: u64 = 126384;
+    const SOLUTION_2: u64 = 154115708116294;
+
+    #[test_case("029A", 68)]
+    #[test_case("980A", 60)]
+    #[test_case("179A", 68)]
+    #[test_case("456A", 64)]
+    #[test_case("379A", 64)]
+    fn test_code(code: &str, presses: u64) {
+        let r = assert_ok!(CostTable::new(2).code(code));
+        assert_eq!(presses, r);
+    }
+
+    #[test]
+    fn test_no_robots() {
+        // "<A^A>^^AvvvA" is typed directly on the numeric keypad's robot
+        let r = assert_ok!(CostTable::new(0).code("029A"));
+        assert_eq!(12, r);
+    }
+
+    #[test]
+    fn test_routes_avoid_gap() {
+        let r = assert_ok!(NUMERIC.routes('A', '1'));
+        assert_eq!(r, vec![vec!['^', '<', '<', 'A']]);
+        let r = assert_ok!(DIRECTIONAL.routes('<', 'A'));
+        assert_eq!(r, vec![vec!['>', '>', '^', 'A']]);
+    }
 
     #[test]
     fn test_1() {
@@ -1,5 +1,5 @@
 use crate::Solver;
-use color_eyre::eyre::{eyre, Result};
+use color_eyre::eyre::{Context, Result};
 
 pub struct Day;
 
@@ -8,34 +8,109 @@ impl Solver for Day {
         Ok(solve_1(input)?.to_string())
     }
 
-    fn part_2(&self, input: &str) -> Result<String> {
-        Ok(solve_2(input)?.to_string())
+    fn part_2(&self, input: &str) -> Result<Option<String>> {
+        Ok(Some(solve_2(input)?.to_string()))
     }
 }
 
-fn solve_1(input: &str) -> Result<usize> {
-    Err(eyre!("not yet implemented"))
+const ITERATIONS: usize = 2000;
+const PRUNE: u64 = 16777216;
+/// Price changes are in `-9..=9`, four of them fit into a base 19 number
+const CHANGES: usize = 19 * 19 * 19 * 19;
+
+fn solve_1(input: &str) -> Result<u64> {
+    Ok(parse_inputs(input)?
+        .into_iter()
+        .map(|s| secrets(s).nth(ITERATIONS - 1).unwrap_or(s))
+        .sum())
+}
+
+fn solve_2(input: &str) -> Result<u64> {
+    let buyers = parse_inputs(input)?;
+
+    // `totals[seq]` is what we'd earn with `seq`, `seen[seq]` marks the last buyer that already
+    // sold on `seq`, as only the first occurrence counts
+    let mut totals = vec![0u64; CHANGES];
+    let mut seen = vec![usize::MAX; CHANGES];
+
+    for (buyer, &secret) in buyers.iter().enumerate() {
+        let prices: Vec<_> = std::iter::once(secret)
+            .chain(secrets(secret).take(ITERATIONS))
+            .map(|s| (s % 10) as usize)
+            .collect();
+
+        let mut seq = 0;
+        for (i, w) in prices.windows(2).enumerate() {
+            // shift in the next change, the oldest one falls out on the left
+            seq = (seq * 19 + (w[1] + 9 - w[0])) % CHANGES;
+            if i >= 3 && seen[seq] != buyer {
+                seen[seq] = buyer;
+                totals[seq] += w[1] as u64;
+            }
+        }
+    }
+
+    Ok(totals.into_iter().max().unwrap_or(0))
 }
-fn solve_2(input: &str) -> Result<usize> {
-    Err(eyre!("not yet implemented"))
+
+fn next_secret(mut secret: u64) -> u64 {
+    secret = ((secret << 6) ^ secret) % PRUNE;
+    secret = ((secret >> 5) ^ secret) % PRUNE;
+    ((secret << 11) ^ secret) % PRUNE
+}
+
+/// The secrets following `secret`, not including itself
+fn secrets(secret: u64) -> impl Iterator<Item = u64> {
+    std::iter::successors(Some(next_secret(secret)), |&s| Some(next_secret(s)))
+}
+
+fn parse_inputs(input: &str) -> Result<Vec<u64>> {
+    input
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(|l| {
+            l.trim()
+                .parse()
+                .wrap_err_with(|| format!("failed to parse {l} to u64"))
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use assert_ok::assert_ok;
-    const INPUT: &str = "";
-    const SOLUTION_1: usize = 0;
-    const SOLUTION_2: usize = 0;
+    const INPUT_1: &str = "1
+10
+100
+2024";
+    const INPUT_2: &str = "1
+2
+3
+2024";
+    const SOLUTION_1: u64 = 37327623;
+    const SOLUTION_2: u64 = 23;
+
+    #[test]
+    fn test_secrets() {
+        let s: Vec<_> = secrets(123).take(10).collect();
+        assert_eq!(
+            s,
+            vec![
+                15887950, 16495136, 527345, 704524, 1553684, 12683156, 11100544, 12249484, 7753432,
+                5908254
+            ]
+        );
+    }
 
     #[test]
     fn test_1() {
-        let r = assert_ok!(solve_1(INPUT));
+        let r = assert_ok!(solve_1(INPUT_1));
         assert_eq!(SOLUTION_1, r);
     }
     #[test]
     fn test_2() {
-        let r = assert_ok!(solve_2(INPUT));
+        let r = assert_ok!(solve_2(INPUT_2));
         assert_eq!(SOLUTION_2, r);
     }
 }
@@ -1,5 +1,6 @@
-use crate::Solver;
+use crate::{graph::Graph, Solver};
 use color_eyre::eyre::{eyre, Result};
+use itertools::Itertools;
 
 pub struct Day;
 
@@ -8,25 +9,81 @@ impl Solver for Day {
         Ok(solve_1(input)?.to_string())
     }
 
-    fn part_2(&self, input: &str) -> Result<String> {
-        Ok(solve_2(input)?.to_string())
+    fn part_2(&self, input: &str) -> Result<Option<String>> {
+        solve_2(input).map(Some)
     }
 }
 
 fn solve_1(input: &str) -> Result<usize> {
-    Err(eyre!("not yet implemented"))
+    let network = Graph::from_edges(false, parse_connections(input)?);
+    Ok(network
+        .triangles()?
+        .into_iter()
+        .filter(|t| t.iter().any(|n| n.starts_with('t')))
+        .count())
 }
-fn solve_2(input: &str) -> Result<usize> {
-    Err(eyre!("not yet implemented"))
+
+fn solve_2(input: &str) -> Result<String> {
+    let network = Graph::from_edges(false, parse_connections(input)?);
+    Ok(network.max_clique()?.into_iter().sorted().join(","))
+}
+
+fn parse_connections(input: &str) -> Result<Vec<(&str, &str)>> {
+    input
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(|l| {
+            l.split_once('-')
+                .map(|(a, b)| (a.trim(), b.trim()))
+                .ok_or_else(|| eyre!("connection {l} is missing a '-'"))
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use assert_ok::assert_ok;
-    const INPUT: &str = "";
-    const SOLUTION_1: usize = 0;
-    const SOLUTION_2: usize = 0;
+    const INPUT: &str = "kh-tc
+qp-kh
+de-cg
+ka-co
+yn-aq
+qp-ub
+cg-tb
+vc-aq
+tb-ka
+wh-tc
+yn-cg
+kh-ub
+ta-co
+de-co
+tc-td
+tb-wq
+wh-td
+ta-ka
+td-qp
+aq-cg
+wq-ub
+ub-vc
+de-ta
+wq-aq
+wq-vc
+wh-yn
+ka-de
+kh-ta
+co-tc
+wh-qp
+tb-vc
+td-yn";
+    const SOLUTION_1: usize = 7;
+    const SOLUTION_2: &str = "co,de,ka,ta";
+
+    #[test]
+    fn test_triangles() {
+        let n = Graph::from_edges(false, assert_ok!(parse_connections(INPUT)));
+        assert_eq!(assert_ok!(n.triangles()).len(), 12);
+    }
 
     #[test]
     fn test_1() {
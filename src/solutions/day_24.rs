@@ -1,5 +1,8 @@
+use std::collections::{BTreeSet, HashMap};
+
 use crate::Solver;
-use color_eyre::eyre::{eyre, Result};
+use color_eyre::eyre::{eyre, Context, Result};
+use itertools::Itertools;
 
 pub struct Day;
 
@@ -8,34 +11,337 @@ impl Solver for Day {
         Ok(solve_1(input)?.to_string())
     }
 
-    fn part_2(&self, input: &str) -> Result<String> {
-        Ok(solve_2(input)?.to_string())
+    fn part_2(&self, input: &str) -> Result<Option<String>> {
+        solve_2(input).map(Some)
     }
 }
 
-fn solve_1(input: &str) -> Result<usize> {
-    Err(eyre!("not yet implemented"))
+fn solve_1(input: &str) -> Result<u64> {
+    Circuit::new(input)?.output()
+}
+
+fn solve_2(input: &str) -> Result<String> {
+    let circuit = Circuit::new(input)?;
+    let swapped = circuit.miswired();
+    if swapped.len() != 8 {
+        return Err(eyre!(
+            "expected four swapped pairs, found {} suspicious wires: {swapped:?}",
+            swapped.len()
+        ));
+    }
+    Ok(swapped.into_iter().join(","))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    And,
+    Or,
+    Xor,
+}
+
+impl Op {
+    fn apply(self, a: bool, b: bool) -> bool {
+        match self {
+            Op::And => a & b,
+            Op::Or => a | b,
+            Op::Xor => a ^ b,
+        }
+    }
+}
+
+impl TryFrom<&str> for Op {
+    type Error = color_eyre::eyre::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "AND" => Ok(Op::And),
+            "OR" => Ok(Op::Or),
+            "XOR" => Ok(Op::Xor),
+            _ => Err(eyre!("unknown gate {value}")),
+        }
+    }
 }
-fn solve_2(input: &str) -> Result<usize> {
-    Err(eyre!("not yet implemented"))
+
+#[derive(Debug, Clone, Copy)]
+struct Gate<'a> {
+    a: &'a str,
+    op: Op,
+    b: &'a str,
+    out: &'a str,
+}
+
+impl Gate<'_> {
+    fn has_input(&self, wire: &str) -> bool {
+        self.a == wire || self.b == wire
+    }
+
+    fn reads_inputs(&self) -> bool {
+        self.a.starts_with(['x', 'y']) && self.b.starts_with(['x', 'y'])
+    }
+}
+
+#[derive(Debug)]
+struct Circuit<'a> {
+    inputs: HashMap<&'a str, bool>,
+    gates: Vec<Gate<'a>>,
+}
+
+impl<'a> Circuit<'a> {
+    fn new(input: &'a str) -> Result<Self> {
+        let (wires, gates) = input
+            .split_once("\n\n")
+            .ok_or_else(|| eyre!("missing blank line between wires and gates"))?;
+
+        let inputs = wires
+            .lines()
+            .map(|l| {
+                let (name, value) = l
+                    .split_once(": ")
+                    .ok_or_else(|| eyre!("invalid wire {l}"))?;
+                match value.trim() {
+                    "0" => Ok((name, false)),
+                    "1" => Ok((name, true)),
+                    v => Err(eyre!("wire {name} has invalid value {v}")),
+                }
+            })
+            .collect::<Result<_>>()?;
+
+        let gates = gates
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(|l| match l.split_whitespace().collect_vec()[..] {
+                [a, op, b, "->", out] => Ok(Gate {
+                    a,
+                    op: op.try_into()?,
+                    b,
+                    out,
+                }),
+                _ => Err(eyre!("invalid gate {l}")),
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { inputs, gates })
+    }
+
+    /// Settles all wires and reads the number off the `z` wires
+    fn output(&self) -> Result<u64> {
+        let mut wires = self.inputs.clone();
+        let mut pending = self.gates.clone();
+
+        while !pending.is_empty() {
+            let before = pending.len();
+            pending.retain(|g| match (wires.get(g.a), wires.get(g.b)) {
+                (Some(&a), Some(&b)) => {
+                    wires.insert(g.out, g.op.apply(a, b));
+                    false
+                }
+                _ => true,
+            });
+            if pending.len() == before {
+                return Err(eyre!("{} gates never get all their inputs", before));
+            }
+        }
+
+        wires
+            .iter()
+            .filter(|(name, _)| name.starts_with('z'))
+            .map(|(name, &v)| {
+                let bit: u32 = name[1..]
+                    .parse()
+                    .wrap_err_with(|| format!("invalid output wire {name}"))?;
+                let mask = 1u64
+                    .checked_shl(bit)
+                    .ok_or_else(|| eyre!("output wire {name} does not fit in 64 bits"))?;
+                Ok(if v { mask } else { 0 })
+            })
+            .sum()
+    }
+
+    /// Checks every gate against the shape of a ripple-carry adder and returns the outputs that
+    /// break it, sorted.
+    ///
+    /// Bit `n` of a ripple-carry adder looks like this, bit 0 is a half adder without `c_in`:
+    ///
+    /// ```text
+    /// x_n XOR y_n -> s_n       x_n AND y_n -> a_n
+    /// s_n XOR c_in -> z_n      s_n AND c_in -> t_n
+    /// a_n OR t_n -> c_out
+    /// ```
+    fn miswired(&self) -> BTreeSet<&'a str> {
+        let last_z = self
+            .gates
+            .iter()
+            .map(|g| g.out)
+            .filter(|o| o.starts_with('z'))
+            .max()
+            .unwrap_or("z00");
+        let is_first = |g: &Gate| g.has_input("x00");
+
+        let feeds = |wire: &str, op: Op| {
+            self.gates
+                .iter()
+                .any(|other| other.op == op && other.has_input(wire))
+        };
+
+        self.gates
+            .iter()
+            .filter(|g| {
+                let z_out = g.out.starts_with('z');
+                match g.op {
+                    // only the final carry leaves through an OR, all other z come from a XOR
+                    _ if z_out && g.out != last_z && g.op != Op::Xor => true,
+                    Op::Or if z_out => false,
+                    // a XOR either adds inputs into s_n, or carries into z_n
+                    Op::Xor if !g.reads_inputs() && !z_out => true,
+                    // s_n has to go on into both z_n and t_n
+                    Op::Xor if g.reads_inputs() && !is_first(g) => !feeds(g.out, Op::Xor),
+                    // a_n and t_n are only ever combined into a carry
+                    Op::And if !is_first(g) => !feeds(g.out, Op::Or),
+                    // a carry goes on into z_n and t_n of the next bit
+                    Op::Or => !feeds(g.out, Op::Xor) || !feeds(g.out, Op::And),
+                    _ => false,
+                }
+            })
+            .map(|g| g.out)
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use assert_ok::assert_ok;
-    const INPUT: &str = "";
-    const SOLUTION_1: usize = 0;
-    const SOLUTION_2: usize = 0;
+    const INPUT_1A: &str = "x00: 1
+x01: 1
+x02: 1
+y00: 0
+y01: 1
+y02: 0
+
+x00 AND y00 -> z00
+x01 XOR y01 -> z01
+x02 OR y02 -> z02";
+
+    const INPUT_1B: &str = "x00: 1
+x01: 0
+x02: 1
+x03: 1
+x04: 0
+y00: 1
+y01: 1
+y02: 1
+y03: 1
+y04: 1
+
+ntg XOR fgs -> mjb
+y02 OR x01 -> tnw
+kwq OR kpj -> z05
+x00 OR x03 -> fst
+tgd XOR rvg -> z01
+vdt OR tnw -> bfw
+bfw AND frj -> z10
+ffh OR nrd -> bqk
+y00 AND y03 -> djm
+y03 OR y00 -> psh
+bqk OR frj -> z08
+tnw OR fst -> frj
+gnj AND tgd -> z11
+bfw XOR mjb -> z00
+x03 OR x00 -> vdt
+gnj AND wpb -> z02
+x04 AND y00 -> kjc
+djm OR pbm -> qhw
+nrd AND vdt -> hwm
+kjc AND fst -> rvg
+y04 OR y02 -> fgs
+y01 AND x02 -> pbm
+ntg OR kjc -> kwq
+psh XOR fgs -> tgd
+qhw XOR tgd -> z09
+pbm OR djm -> kpj
+x03 XOR y03 -> ffh
+x00 XOR y04 -> ntg
+bfw OR bqk -> z06
+nrd XOR fgs -> wpb
+frj XOR qhw -> z04
+bqk OR frj -> z07
+y03 OR x01 -> nrd
+hwm AND bqk -> z03
+tgd XOR rvg -> z12
+tnw OR pbm -> gnj";
+
+    const SOLUTION_1A: u64 = 4;
+    const SOLUTION_1B: u64 = 2024;
+
+    /// Builds a correct ripple-carry adder for `bits` bits, with `x + y` set on the inputs
+    fn adder(bits: usize, x: u64, y: u64) -> String {
+        let mut out = String::new();
+        for i in 0..bits {
+            out += &format!("x{i:02}: {}\n", (x >> i) & 1);
+        }
+        for i in 0..bits {
+            out += &format!("y{i:02}: {}\n", (y >> i) & 1);
+        }
+        out += "\n";
+        out += "x00 XOR y00 -> z00\nx00 AND y00 -> c00\n";
+        for i in 1..bits {
+            let c_in = format!("c{:02}", i - 1);
+            let c_out = if i == bits - 1 {
+                format!("z{bits:02}")
+            } else {
+                format!("c{i:02}")
+            };
+            out += &format!("x{i:02} XOR y{i:02} -> s{i:02}\n");
+            out += &format!("x{i:02} AND y{i:02} -> a{i:02}\n");
+            out += &format!("{c_in} XOR s{i:02} -> z{i:02}\n");
+            out += &format!("s{i:02} AND {c_in} -> t{i:02}\n");
+            out += &format!("a{i:02} OR t{i:02} -> {c_out}\n");
+        }
+        out
+    }
+
+    fn swap(circuit: &str, a: &str, b: &str) -> String {
+        circuit
+            .replace(&format!("-> {a}\n"), "-> __swap__\n")
+            .replace(&format!("-> {b}\n"), &format!("-> {a}\n"))
+            .replace("-> __swap__\n", &format!("-> {b}\n"))
+    }
 
     #[test]
     fn test_1() {
-        let r = assert_ok!(solve_1(INPUT));
-        assert_eq!(SOLUTION_1, r);
+        let r = assert_ok!(solve_1(INPUT_1A));
+        assert_eq!(SOLUTION_1A, r);
+        let r = assert_ok!(solve_1(INPUT_1B));
+        assert_eq!(SOLUTION_1B, r);
+    }
+
+    #[test]
+    fn test_adder() {
+        let input = adder(12, 1234, 3000);
+        let r = assert_ok!(solve_1(&input));
+        assert_eq!(4234, r);
+        let c = assert_ok!(Circuit::new(&input));
+        assert!(c.miswired().is_empty());
+    }
+
+    #[test]
+    fn test_output_too_wide() {
+        let input = "x00: 1\ny00: 0\n\nx00 OR y00 -> z64";
+        assert!(solve_1(input).is_err());
     }
+
     #[test]
     fn test_2() {
-        let r = assert_ok!(solve_2(INPUT));
-        assert_eq!(SOLUTION_2, r);
+        let mut input = adder(12, 0, 0);
+        for (a, b) in [
+            ("z03", "t03"),
+            ("s05", "a05"),
+            ("z07", "c07"),
+            ("z10", "s10"),
+        ] {
+            input = swap(&input, a, b);
+        }
+        let r = assert_ok!(solve_2(&input));
+        assert_eq!("a05,c07,s05,s10,t03,z03,z07,z10", r);
     }
 }
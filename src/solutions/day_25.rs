@@ -8,25 +8,111 @@ impl Solver for Day {
         Ok(solve_1(input)?.to_string())
     }
 
-    fn part_2(&self, input: &str) -> Result<String> {
-        Ok(solve_2(input)?.to_string())
+    fn part_2(&self, _input: &str) -> Result<Option<String>> {
+        Ok(None)
     }
 }
 
+const WIDTH: usize = 5;
+const HEIGHT: usize = 7;
+
 fn solve_1(input: &str) -> Result<usize> {
-    Err(eyre!("not yet implemented"))
+    let (locks, keys) = parse_inputs(input)?;
+
+    Ok(locks
+        .iter()
+        .map(|lock| keys.iter().filter(|&key| lock & key == 0).count())
+        .sum())
 }
-fn solve_2(input: &str) -> Result<usize> {
-    Err(eyre!("not yet implemented"))
+
+/// Every schematic becomes a 35 bit mask of its filled cells, one bit per cell in reading order.
+/// A key fits a lock if no cell is filled in both, no need to look at column heights at all.
+fn parse_inputs(input: &str) -> Result<(Vec<u64>, Vec<u64>)> {
+    let mut locks = vec![];
+    let mut keys = vec![];
+
+    for block in input.split("\n\n").filter(|b| !b.trim().is_empty()) {
+        let lines: Vec<_> = block.lines().filter(|l| !l.is_empty()).collect();
+        if lines.len() != HEIGHT || lines.iter().any(|l| l.len() != WIDTH) {
+            return Err(eyre!("schematic is not {WIDTH}x{HEIGHT}:\n{block}"));
+        }
+
+        let mut mask = 0u64;
+        for c in lines.iter().flat_map(|l| l.chars()) {
+            mask <<= 1;
+            match c {
+                '#' => mask |= 1,
+                '.' => (),
+                _ => return Err(eyre!("unexpected character {c:?} in schematic")),
+            }
+        }
+
+        // locks have their top row filled, keys their bottom row
+        match (lines[0], lines[HEIGHT - 1]) {
+            ("#####", ".....") => locks.push(mask),
+            (".....", "#####") => keys.push(mask),
+            _ => return Err(eyre!("schematic is neither lock nor key:\n{block}")),
+        }
+    }
+
+    Ok((locks, keys))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Part;
     use assert_ok::assert_ok;
-    const INPUT: &str = "";
-    const SOLUTION_1: usize = 0;
-    const SOLUTION_2: usize = 0;
+    const INPUT: &str = "#####
+.####
+.####
+.####
+.#.#.
+.#...
+.....
+
+#####
+##.##
+.#.##
+...##
+...#.
+...#.
+.....
+
+.....
+#....
+#....
+#...#
+#.#.#
+#.###
+#####
+
+.....
+.....
+#.#..
+###..
+###.#
+###.#
+#####
+
+.....
+.....
+.....
+#....
+#.#..
+#.#.#
+#####";
+    const SOLUTION_1: usize = 3;
+
+    #[test]
+    fn test_parse() {
+        let (locks, keys) = assert_ok!(parse_inputs(INPUT));
+        assert_eq!(locks.len(), 2);
+        assert_eq!(keys.len(), 3);
+
+        let (locks, keys) = assert_ok!(parse_inputs(&format!("{INPUT}\n\n")));
+        assert_eq!((locks.len(), keys.len()), (2, 3));
+    }
 
     #[test]
     fn test_1() {
@@ -34,8 +120,8 @@ mod tests {
         assert_eq!(SOLUTION_1, r);
     }
     #[test]
-    fn test_2() {
-        let r = assert_ok!(solve_2(INPUT));
-        assert_eq!(SOLUTION_2, r);
+    fn test_no_part_2() {
+        let r = assert_ok!(Day.run_part(INPUT, Part::Part2));
+        assert_eq!(r, None);
     }
 }
@@ -0,0 +1,162 @@
+//! Disjoint sets over `0..n`, with path compression and union by rank.
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+    /// Only meaningful for roots
+    size: Vec<usize>,
+    count: usize,
+}
+
+impl UnionFind {
+    /// `n` singleton sets
+    pub fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            rank: vec![0; n],
+            size: vec![1; n],
+            count: n,
+        }
+    }
+
+    /// Adds a new singleton set and returns its element
+    pub fn add(&mut self) -> usize {
+        let id = self.parent.len();
+        self.parent.push(id);
+        self.rank.push(0);
+        self.size.push(1);
+        self.count += 1;
+        id
+    }
+
+    /// The representative of the set `x` is in
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut x = x;
+        while self.parent[x] != root {
+            x = std::mem::replace(&mut self.parent[x], root);
+        }
+        root
+    }
+
+    /// Joins the sets of `a` and `b`, false if they already were the same set
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        let (big, small) = if self.rank[a] < self.rank[b] {
+            (b, a)
+        } else {
+            (a, b)
+        };
+        self.parent[small] = big;
+        self.size[big] += self.size[small];
+        if self.rank[big] == self.rank[small] {
+            self.rank[big] += 1;
+        }
+        self.count -= 1;
+        true
+    }
+
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Size of the set `x` is in
+    pub fn size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    /// Number of elements
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Number of disjoint sets
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Every set with its elements in ascending order, sets ordered by their smallest element
+    pub fn components(&mut self) -> Vec<Vec<usize>> {
+        let mut index = HashMap::new();
+        let mut components: Vec<Vec<usize>> = vec![];
+        for x in 0..self.len() {
+            let root = self.find(x);
+            let i = *index.entry(root).or_insert_with(|| {
+                components.push(vec![]);
+                components.len() - 1
+            });
+            components[i].push(x);
+        }
+        components
+    }
+
+    /// Sizes of all sets, largest first
+    pub fn sizes(&self) -> Vec<usize> {
+        let mut sizes: Vec<_> = (0..self.len())
+            .filter(|&x| self.parent[x] == x)
+            .map(|x| self.size[x])
+            .collect();
+        sizes.sort_unstable_by(|a, b| b.cmp(a));
+        sizes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_union_find() {
+        let mut uf = UnionFind::new(6);
+        assert!(uf.union(0, 1));
+        assert!(uf.union(4, 3));
+        assert!(uf.union(1, 4));
+        assert!(!uf.union(0, 3));
+        assert!(uf.same(0, 3));
+        assert!(!uf.same(0, 2));
+        assert_eq!(uf.size(3), 4);
+        assert_eq!(uf.count(), 3);
+        assert_eq!(uf.components(), vec![vec![0, 1, 3, 4], vec![2], vec![5]]);
+        assert_eq!(uf.sizes(), vec![4, 1, 1]);
+
+        let x = uf.add();
+        uf.union(x, 2);
+        assert_eq!(uf.count(), 3);
+        assert_eq!(uf.size(6), 2);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_matches_labels(n in 1usize..30, pairs in proptest::collection::vec((0usize..30, 0usize..30), 0..40)) {
+            let mut uf = UnionFind::new(n);
+            let mut label: Vec<usize> = (0..n).collect();
+            for (a, b) in pairs.into_iter().filter(|&(a, b)| a < n && b < n) {
+                let (la, lb) = (label[a], label[b]);
+                prop_assert_eq!(uf.union(a, b), la != lb);
+                label.iter_mut().filter(|l| **l == lb).for_each(|l| *l = la);
+            }
+            for a in 0..n {
+                prop_assert_eq!(uf.size(a), label.iter().filter(|&&l| l == label[a]).count());
+                for b in 0..n {
+                    prop_assert_eq!(uf.same(a, b), label[a] == label[b]);
+                }
+            }
+            prop_assert_eq!(uf.components().len(), uf.count());
+        }
+    }
+}